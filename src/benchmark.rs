@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::graph::Graph;
+use crate::grid::{CostModel, Direction, Grid};
+use crate::map;
+use crate::map::MapError;
+use crate::path::PathFinding;
+
+// One line of a Moving AI scenario file. Start and goal are (row, col), the file itself stores x before y.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Scenario {
+    pub bucket: usize,
+    pub map: String,
+    pub width: usize,
+    pub height: usize,
+    pub start: (usize, usize),
+    pub goal: (usize, usize),
+    pub optimal: f32,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Outcome {
+    pub scenario: Scenario,
+    // Octile length of the found path, None if no path was found.
+    pub length: Option<f32>,
+    // None, if the search does not report its expansions.
+    pub expanded: Option<usize>,
+    pub duration: Duration,
+}
+
+impl Outcome {
+    pub fn matches(&self, tolerance: f32) -> bool {
+        return self.length.is_some_and(|length| (length - self.scenario.optimal).abs() <= tolerance);
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Report {
+    pub name: String,
+    pub outcomes: Vec<Outcome>,
+}
+
+impl Report {
+    pub fn mismatches(&self, tolerance: f32) -> Vec<&Outcome> {
+        return self.outcomes.iter().filter(|outcome| !outcome.matches(tolerance)).collect();
+    }
+
+    // None, if any outcome misses its expansions.
+    pub fn expanded(&self) -> Option<usize> {
+        return self.outcomes.iter().map(|outcome| outcome.expanded).sum();
+    }
+
+    pub fn duration(&self) -> Duration {
+        return self.outcomes.iter().map(|outcome| outcome.duration).sum();
+    }
+
+    pub fn summary(&self, tolerance: f32) -> String {
+        let expanded = self.expanded().map_or(String::from("unknown"), |expanded| expanded.to_string());
+        return format!("{}: {} scenarios, {} mismatches, {expanded} expanded, {:?}",
+                       self.name, self.outcomes.len(), self.mismatches(tolerance).len(), self.duration());
+    }
+}
+
+pub fn scenarios(text: &str) -> Result<Vec<Scenario>, MapError> {
+    let mut scenarios = Vec::new();
+
+    for (line_number, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with("version") {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 9 {
+            return Err(MapError::Scenario(line_number));
+        }
+
+        let number = |field: usize| fields[field].trim().parse::<usize>().map_err(|_| MapError::Scenario(line_number));
+        scenarios.push(Scenario {
+            bucket: number(0)?,
+            map: fields[1].to_string(),
+            width: number(2)?,
+            height: number(3)?,
+            start: (number(5)?, number(4)?),
+            goal: (number(7)?, number(6)?),
+            optimal: fields[8].trim().parse::<f32>().map_err(|_| MapError::Scenario(line_number))?,
+        });
+    }
+
+    return Ok(scenarios);
+}
+
+pub fn load_scenarios(path: impl AsRef<Path>) -> Result<Vec<Scenario>, MapError> {
+    return scenarios(&fs::read_to_string(path).map_err(|error| MapError::Io(error.to_string()))?);
+}
+
+// Graph of the map with the moves the benchmark allows. Straight steps cost 1 and diagonal steps the square root of 2,
+// diagonal steps must not cut a blocked corner. Node ids are the grid's node ids, positioned at (row, column, 0).
+pub fn octile_graph(grid: &Grid, directions: &[Direction]) -> Graph {
+    let mut graph = Graph::from_grid(grid, directions, CostModel::Distance);
+    let edges: Vec<_> = graph.edges.iter().filter(|edge| {
+        let ((row1, col1), (row2, col2)) = (grid.coords(edge.source), grid.coords(edge.destination));
+        return grid.walkable(grid.node_id((row1, col2))) && grid.walkable(grid.node_id((row2, col1)));
+    }).cloned().collect();
+
+    let positions = graph.node_position_lookup.take().unwrap_or_default();
+    graph = Graph::from(edges);
+    graph.add_nodes(positions.keys().cloned());
+    graph.offer_positions(positions);
+    return graph;
+}
+
+// Searches run on the octile graph, so any search may find the optimal length of the benchmark. Scenarios made for
+// another map size, or with start or goal outside of the map, are mismatches without a path.
+pub fn run(name: &str,
+           path_finding: &dyn PathFinding,
+           grid: &Grid,
+           directions: &[Direction],
+           scenarios: &[Scenario]) -> Report {
+    let graph = octile_graph(grid, directions);
+
+    let outcomes = scenarios.iter().map(|scenario| {
+        let inside = |(row, col): (usize, usize)| row < grid.height && col < grid.width;
+        if (scenario.width, scenario.height) != (grid.width, grid.height) || !inside(scenario.start) || !inside(scenario.goal) {
+            return Outcome { scenario: scenario.clone(), length: None, expanded: Some(0), duration: Duration::ZERO };
+        }
+
+        let (start, goal) = (grid.node_id(scenario.start), grid.node_id(scenario.goal));
+        if start == goal {
+            return Outcome { scenario: scenario.clone(), length: Some(0.0), expanded: Some(0), duration: Duration::ZERO };
+        }
+
+        let (start_node, goal_node) = match (graph.nodes_lookup.get(&start), graph.nodes_lookup.get(&goal)) {
+            (Some(start_node), Some(goal_node)) => (start_node.clone(), goal_node.clone()),
+            _ => return Outcome { scenario: scenario.clone(), length: None, expanded: Some(0), duration: Duration::ZERO }
+        };
+
+        let started = Instant::now();
+        let traced = path_finding.graph_traced(start_node, goal_node, &graph);
+        let duration = started.elapsed();
+
+        return Outcome {
+            scenario: scenario.clone(),
+            length: if traced.path.edges.is_empty() { None } else { Some(traced.path.edges.iter().map(|edge| edge.weight).sum()) },
+            expanded: traced.expanded.map(|expanded| expanded.len()),
+            duration,
+        };
+    }).collect();
+
+    return Report { name: name.to_string(), outcomes };
+}
+
+// Runs every search on a scenario file. Maps are looked up next to the scenario file, by their path and by file name.
+pub fn run_file(path: impl AsRef<Path>,
+                searches: &[(&str, &dyn PathFinding)],
+                directions: &[Direction]) -> Result<Vec<Report>, MapError> {
+    let path = path.as_ref();
+    let folder = path.parent().unwrap_or(Path::new(""));
+    let scenarios = load_scenarios(path)?;
+
+    // maps in order of their first scenario
+    let mut maps: Vec<&str> = Vec::new();
+    let mut by_map: HashMap<&str, Vec<Scenario>> = HashMap::new();
+    for scenario in &scenarios {
+        if !by_map.contains_key(scenario.map.as_str()) {
+            maps.push(&scenario.map);
+        }
+        by_map.entry(&scenario.map).or_default().push(scenario.clone());
+    }
+
+    let mut reports: Vec<Report> = searches.iter().map(|(name, _)| Report { name: name.to_string(), outcomes: Vec::new() }).collect();
+    for map_name in maps {
+        let map_path = folder.join(map_name);
+        let grid = match map_path.exists() {
+            true => map::load_moving_ai(map_path)?,
+            false => map::load_moving_ai(folder.join(Path::new(map_name).file_name().unwrap_or_default()))?
+        };
+
+        for (report, (name, path_finding)) in reports.iter_mut().zip(searches) {
+            report.outcomes.extend(run(name, *path_finding, &grid, directions, &by_map[map_name]).outcomes);
+        }
+    }
+
+    return Ok(reports);
+}
+
+
+#[cfg(test)]
+use crate::search::{AStar, Dijkstra, euclidean_distance};
+#[cfg(test)]
+use crate::search::breadth_first::BreadthFirstSearch;
+
+#[cfg(test)]
+fn all() -> Vec<Direction> {
+    return vec![Direction::Up, Direction::Down, Direction::Left, Direction::Right,
+                Direction::UpLeft, Direction::UpRight, Direction::DownLeft, Direction::DownRight];
+}
+
+#[cfg(test)]
+const MAP: &str = "type octile\nheight 3\nwidth 4\nmap\n....\n.@@.\n....\n";
+
+#[cfg(test)]
+const SCENARIOS: &str = "version 1\n\
+    0\tsmall.map\t4\t3\t0\t0\t3\t0\t3.00000000\n\
+    0\tsmall.map\t4\t3\t0\t0\t0\t2\t2.00000000\n\
+    1\tsmall.map\t4\t3\t0\t0\t3\t2\t5.00000000\n";
+
+#[test]
+fn scenarios_should_be_parsed() {
+    let parsed = scenarios(SCENARIOS).unwrap();
+
+    assert_eq!(3, parsed.len());
+    assert_eq!((2, 3), parsed[2].goal);
+    assert_eq!("small.map", parsed[0].map);
+    assert_eq!(Some(MapError::Scenario(1)), scenarios("version 1\n0\tsmall.map\t4\n").err());
+}
+
+#[test]
+fn octile_graph_should_not_cut_corners() {
+    let grid = map::moving_ai(MAP).unwrap();
+
+    let graph = octile_graph(&grid, &all());
+    let diagonal = |from: (usize, usize), to: (usize, usize)| graph.edges.iter()
+        .find(|edge| edge.source == grid.node_id(from) && edge.destination == grid.node_id(to))
+        .map(|edge| edge.weight);
+
+    assert_eq!(None, diagonal((0, 2), (1, 3)));
+    assert_eq!(None, diagonal((1, 0), (2, 1)));
+    assert_eq!(Some(1.0), diagonal((0, 3), (1, 3)));
+    assert!(graph.edges.iter().all(|edge| edge.weight == 1.0));
+
+    let open = octile_graph(&Grid::from(&[&[1.0, 1.0], &[1.0, 1.0]]), &all());
+    assert_eq!(12, open.edges.len());
+    assert_eq!(4, open.edges.iter().filter(|edge| edge.weight == 2.0_f32.sqrt()).count());
+}
+
+#[test]
+fn run_should_compare_against_optimal_length() {
+    let grid = map::moving_ai(MAP).unwrap();
+    let scenarios = scenarios(SCENARIOS).unwrap();
+    let a_star = AStar { heuristic: Box::from(euclidean_distance) };
+
+    let report = run("a*", &a_star, &grid, &all(), &scenarios);
+    let dijkstra = run("dijkstra", &Dijkstra {}, &grid, &all(), &scenarios);
+
+    assert_eq!(Some(5.0), report.outcomes[2].length);
+    assert!(report.mismatches(0.001).is_empty());
+    assert!(dijkstra.mismatches(0.001).is_empty());
+    assert!(report.expanded().is_some_and(|expanded| expanded > 0 && expanded <= dijkstra.expanded().unwrap()));
+    assert!(report.summary(0.001).starts_with("a*: 3 scenarios, 0 mismatches"));
+}
+
+#[test]
+fn run_should_report_unknown_expansions() {
+    struct Untraced {}
+    impl PathFinding for Untraced {
+        fn graph(&self, source: crate::node::Node, target: crate::node::Node, graph: &Graph) -> Graph {
+            return Dijkstra {}.graph(source, target, graph);
+        }
+
+        fn grid(&self, source: (usize, usize), target: (usize, usize), grid: &Grid, directions: &[Direction]) -> Graph {
+            return Dijkstra {}.grid(source, target, grid, directions);
+        }
+    }
+
+    let report = run("untraced", &Untraced {}, &map::moving_ai(MAP).unwrap(), &all(), &scenarios(SCENARIOS).unwrap());
+
+    assert!(report.mismatches(0.001).is_empty());
+    assert_eq!(None, report.expanded());
+    assert!(report.summary(0.001).contains("unknown expanded"));
+}
+
+#[test]
+fn run_should_mismatch_scenarios_outside_of_the_map() {
+    let grid = map::moving_ai(MAP).unwrap();
+    let scenarios = scenarios("version 1\n\
+        0\tsmall.map\t4\t3\t0\t0\t4\t0\t4.00000000\n\
+        0\tsmall.map\t4\t3\t0\t3\t0\t0\t3.00000000\n\
+        0\tsmall.map\t5\t3\t0\t0\t3\t0\t3.00000000\n").unwrap();
+
+    let report = run("dijkstra", &Dijkstra {}, &grid, &all(), &scenarios);
+
+    assert_eq!(3, report.mismatches(0.001).len());
+    assert!(report.outcomes.iter().all(|outcome| outcome.length.is_none()));
+}
+
+#[test]
+fn run_file_should_report_every_search() {
+    let folder = std::env::temp_dir().join(format!("path-finding-benchmark-{}", std::process::id()));
+    fs::create_dir_all(&folder).unwrap();
+    fs::write(folder.join("small.map"), MAP).unwrap();
+    fs::write(folder.join("small.map.scen"), SCENARIOS).unwrap();
+
+    let a_star = AStar { heuristic: Box::from(euclidean_distance) };
+    let searches: [(&str, &dyn PathFinding); 3] = [("dijkstra", &Dijkstra {}), ("a*", &a_star), ("bfs", &BreadthFirstSearch {})];
+    let reports = run_file(folder.join("small.map.scen"), &searches, &all()).unwrap();
+    fs::remove_dir_all(&folder).unwrap();
+
+    assert_eq!(vec!["dijkstra", "a*", "bfs"], reports.iter().map(|report| report.name.as_str()).collect::<Vec<&str>>());
+    assert!(reports.iter().all(|report| report.outcomes.len() == 3));
+    assert!(reports[0].mismatches(0.001).is_empty() && reports[1].mismatches(0.001).is_empty());
+    assert!(reports[1].expanded() <= reports[0].expanded());
+}
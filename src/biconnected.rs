@@ -0,0 +1,227 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::graph::{Edge, Graph};
+use crate::grid::{CostModel, Direction, Grid};
+#[cfg(test)]
+use crate::search::cost::INFINITY;
+use crate::spanning::NodeIndex;
+
+// Edges are treated as undirected. Parallel edges, such as (a, b) and (b, a), are treated as a single connection,
+// hence a bridge or component contains every edge of the graph between its two nodes.
+struct Biconnectivity {
+    bridges: Vec<Edge>,
+    articulation_points: Vec<usize>,
+    components: Vec<Graph>,
+}
+
+pub fn bridges(graph: &Graph) -> Vec<Edge> {
+    return biconnectivity(graph).bridges;
+}
+
+pub fn articulation_points(graph: &Graph) -> Vec<usize> {
+    return biconnectivity(graph).articulation_points;
+}
+
+pub fn biconnected_components(graph: &Graph) -> Vec<Graph> {
+    return biconnectivity(graph).components;
+}
+
+pub fn grid_chokepoints(grid: &Grid, directions: &[Direction]) -> Vec<(usize, usize)> {
+    return articulation_points(&Graph::from_grid(grid, directions, CostModel::Destination)).into_iter()
+        .map(|node_id| grid.coords(node_id))
+        .collect();
+}
+
+fn biconnectivity(graph: &Graph) -> Biconnectivity {
+    let node_index = NodeIndex::from(graph);
+    let node_count = node_index.ids.len();
+
+    let mut pair_edges: HashMap<(usize, usize), Vec<Edge>> = HashMap::new();
+    let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); node_count];
+
+    for edge in &graph.edges {
+        let (source, destination) = (node_index.index(edge.source), node_index.index(edge.destination));
+        if source == destination {
+            continue;
+        }
+
+        let pair = (source.min(destination), source.max(destination));
+        let pair_edges = pair_edges.entry(pair).or_default();
+        if pair_edges.is_empty() {
+            adjacency[source].push(destination);
+            adjacency[destination].push(source);
+        }
+        pair_edges.push(edge.clone());
+    }
+
+    let mut discovery: Vec<Option<usize>> = vec![None; node_count];
+    let mut low: Vec<usize> = vec![0; node_count];
+    let mut counter = 0;
+
+    let mut bridges = Vec::new();
+    let mut articulation: HashSet<usize> = HashSet::new();
+    let mut components = Vec::new();
+    let mut edge_stack: Vec<(usize, usize)> = Vec::new();
+
+    for root in 0..node_count {
+        if discovery[root].is_some() {
+            continue;
+        }
+
+        discovery[root] = Some(counter);
+        low[root] = counter;
+        counter += 1;
+        let mut root_children = 0;
+
+        // (node, parent, position of the next neighbour to visit)
+        let mut call_stack: Vec<(usize, Option<usize>, usize)> = vec![(root, None, 0)];
+
+        while let Some((node, parent, next)) = call_stack.pop() {
+            if next < adjacency[node].len() {
+                call_stack.push((node, parent, next + 1));
+                let neighbour = adjacency[node][next];
+
+                match discovery[neighbour] {
+                    None => {
+                        discovery[neighbour] = Some(counter);
+                        low[neighbour] = counter;
+                        counter += 1;
+                        edge_stack.push((node, neighbour));
+                        call_stack.push((neighbour, Some(node), 0));
+
+                        if node == root {
+                            root_children += 1;
+                        }
+                    }
+                    Some(neighbour_discovery) => {
+                        if Some(neighbour) != parent && neighbour_discovery < discovery[node].unwrap() {
+                            low[node] = low[node].min(neighbour_discovery);
+                            edge_stack.push((node, neighbour));
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let parent = match parent {
+                None => continue,
+                Some(parent) => parent
+            };
+
+            low[parent] = low[parent].min(low[node]);
+            let parent_discovery = discovery[parent].unwrap();
+
+            if low[node] > parent_discovery {
+                bridges.extend(pair_edges[&(parent.min(node), parent.max(node))].iter().cloned());
+            }
+
+            if low[node] >= parent_discovery {
+                if parent != root {
+                    articulation.insert(node_index.ids[parent]);
+                }
+
+                let mut component_edges = Vec::new();
+                while let Some((source, destination)) = edge_stack.pop() {
+                    component_edges.extend(pair_edges[&(source.min(destination), source.max(destination))].iter().cloned());
+                    if (source, destination) == (parent, node) {
+                        break;
+                    }
+                }
+                components.push(Graph::from(component_edges));
+            }
+        }
+
+        if root_children > 1 {
+            articulation.insert(node_index.ids[root]);
+        }
+    }
+
+    let mut articulation_points: Vec<usize> = articulation.into_iter().collect();
+    articulation_points.sort();
+
+    return Biconnectivity {
+        bridges,
+        articulation_points,
+        components,
+    };
+}
+
+
+#[cfg(test)]
+fn two_rooms() -> Graph {
+    // triangle 0-1-2, corridor 2-3, triangle 3-4-5, dead end 5-6
+    return Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 1.0),
+        Edge::from(1, 1, 2, 1.0),
+        Edge::from(2, 2, 0, 1.0),
+        Edge::from(3, 2, 3, 1.0),
+        Edge::from(4, 3, 2, 1.0),
+        Edge::from(5, 3, 4, 1.0),
+        Edge::from(6, 4, 5, 1.0),
+        Edge::from(7, 5, 3, 1.0),
+        Edge::from(8, 5, 6, 1.0),
+    ]));
+}
+
+#[test]
+fn bridges_should_return_corridor_edges() {
+    let bridges = bridges(&two_rooms());
+    let mut pairs: Vec<(usize, usize)> = bridges.iter().map(|edge| (edge.source, edge.destination)).collect();
+    pairs.sort();
+
+    assert_eq!(vec![(2, 3), (3, 2), (5, 6)], pairs);
+}
+
+#[test]
+fn articulation_points_should_return_chokepoint_nodes() {
+    assert_eq!(vec![2, 3, 5], articulation_points(&two_rooms()));
+}
+
+#[test]
+fn articulation_points_should_include_root_with_multiple_children() {
+    let star = Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 1.0),
+        Edge::from(1, 0, 2, 1.0),
+        Edge::from(2, 0, 3, 1.0),
+    ]));
+
+    assert_eq!(vec![0], articulation_points(&star));
+}
+
+#[test]
+fn cycle_should_not_have_bridges_or_articulation_points() {
+    let cycle = Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 1.0),
+        Edge::from(1, 1, 2, 1.0),
+        Edge::from(2, 2, 3, 1.0),
+        Edge::from(3, 3, 0, 1.0),
+    ]));
+
+    assert!(bridges(&cycle).is_empty());
+    assert!(articulation_points(&cycle).is_empty());
+    assert_eq!(1, biconnected_components(&cycle).len());
+}
+
+#[test]
+fn biconnected_components_should_split_at_articulation_points() {
+    let components = biconnected_components(&two_rooms());
+    let mut sizes: Vec<(usize, usize)> = components.iter()
+        .map(|component| (component.node_count, component.edges.len()))
+        .collect();
+    sizes.sort();
+
+    assert_eq!(vec![(2, 1), (2, 2), (3, 3), (3, 3)], sizes);
+}
+
+#[test]
+fn grid_chokepoints_should_return_door_cells() {
+    let grid = Grid::from(&[
+        &[0.0, 0.0, INFINITY, 0.0, 0.0],
+        &[0.0, 0.0, 0.0, 0.0, 0.0],
+        &[0.0, 0.0, INFINITY, 0.0, 0.0],
+    ]);
+
+    let chokepoints = grid_chokepoints(&grid, &[Direction::Up, Direction::Down, Direction::Left, Direction::Right]);
+
+    assert_eq!(vec![(1, 1), (1, 2), (1, 3)], chokepoints);
+}
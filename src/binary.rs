@@ -0,0 +1,92 @@
+#[cfg(test)]
+use std::collections::HashMap;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+use crate::graph::{Edge, Graph};
+#[cfg(test)]
+use crate::grid::Grid;
+#[cfg(test)]
+use crate::node::Vec3;
+#[cfg(test)]
+use crate::search::cost;
+
+// Every encoding starts with these bytes, the last one being the format version.
+const HEADER: &[u8] = b"PFB\x01";
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum BinaryError {
+    // Not written by to_bytes, or by an incompatible version.
+    Header,
+    Encoding(String),
+}
+
+// Compact little-endian encoding of any serializable value, e.g. graphs, grids and paths.
+pub fn to_bytes<T: Serialize>(value: &T) -> Result<Vec<u8>, BinaryError> {
+    let mut bytes = HEADER.to_vec();
+    bincode::serialize_into(&mut bytes, value).map_err(|error| BinaryError::Encoding(error.to_string()))?;
+    return Ok(bytes);
+}
+
+pub fn from_bytes<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, BinaryError> {
+    let body = bytes.strip_prefix(HEADER).ok_or(BinaryError::Header)?;
+    return bincode::deserialize(body).map_err(|error| BinaryError::Encoding(error.to_string()));
+}
+
+
+#[cfg(test)]
+fn graph() -> Graph {
+    let mut graph = Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 2.5),
+        Edge::from(3, 1, 2, 1.0),
+    ]));
+    graph.offer_positions(HashMap::from([
+        (0, Vec3::from(0.0, 0.0, 1.0)),
+        (1, Vec3::from(2.0, 0.5, 0.0)),
+        (2, Vec3::from(3.0, 1.5, 0.0)),
+    ]));
+
+    return graph;
+}
+
+#[test]
+fn graph_should_survive_binary_round_trip() {
+    let graph = graph();
+
+    let restored: Graph = from_bytes(&to_bytes(&graph).unwrap()).unwrap();
+
+    assert!(graph.edges == restored.edges);
+    assert_eq!(1, restored.edges_lookup[&3].source);
+    assert_eq!(3, restored.node_count);
+    assert_eq!(2.5, restored.edges[0].weight);
+    assert_eq!(&Vec3::from(0.0, 0.0, 1.0), restored.get_position(&0));
+}
+
+#[test]
+fn grid_should_survive_binary_round_trip() {
+    let mut grid = Grid::from(&[&[1.0, 2.0], &[cost::INFINITY, 4.0]]);
+    grid.block((0, 1));
+
+    let restored: Grid = from_bytes(&to_bytes(&grid).unwrap()).unwrap();
+
+    assert_eq!(grid.costs, restored.costs);
+    assert_eq!((2, 2, 4), (restored.width, restored.height, restored.size));
+    assert_eq!(0, restored.version());
+}
+
+#[test]
+fn binary_should_be_smaller_than_json() {
+    let graph = graph();
+
+    assert!(to_bytes(&graph).unwrap().len() < serde_json::to_vec(&graph).unwrap().len());
+}
+
+#[test]
+fn from_bytes_should_reject_foreign_data() {
+    let bytes = to_bytes(&graph()).unwrap();
+
+    assert_eq!(Some(BinaryError::Header), from_bytes::<Graph>(b"{}").err());
+    assert!(matches!(from_bytes::<Graph>(&bytes[..bytes.len() - 3]), Err(BinaryError::Encoding(_))));
+}
@@ -0,0 +1,324 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::graph::{Edge, Graph};
+use crate::grid::{CellChange, Direction, Grid};
+use crate::node::Node;
+use crate::path::PathFinding;
+use crate::query::QueryOptions;
+#[cfg(test)]
+use crate::search::Dijkstra;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum CacheKey {
+    Graph(usize, usize),
+    Grid(usize, usize, u8),
+}
+
+struct CacheEntry {
+    edges: Vec<Edge>,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<CacheKey, CacheEntry>,
+    tick: u64,
+    grid_version: Option<usize>,
+    hits: usize,
+    misses: usize,
+}
+
+// Memoizes the paths found by the wrapped path finding, evicting the least recently used path once the capacity is
+// reached. Meant to be used with a single graph or grid: grid changes are picked up through the grid's change log,
+// graph changes have to be reported with edge_changed. Queries with options are not cached.
+pub struct PathCache {
+    pub path_finding: Box<dyn PathFinding>,
+    capacity: usize,
+    state: RefCell<CacheState>,
+}
+
+impl PathCache {
+    pub fn from(path_finding: Box<dyn PathFinding>, capacity: usize) -> PathCache {
+        return PathCache {
+            path_finding,
+            capacity,
+            state: RefCell::new(CacheState::default()),
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        return self.state.borrow().entries.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len() == 0;
+    }
+
+    pub fn hits(&self) -> usize {
+        return self.state.borrow().hits;
+    }
+
+    pub fn misses(&self) -> usize {
+        return self.state.borrow().misses;
+    }
+
+    pub fn clear(&self) {
+        self.state.borrow_mut().entries.clear();
+    }
+
+    pub fn in_graph(&self, source: usize, target: usize, graph: &Graph) -> Graph {
+        return match (graph.nodes_lookup.get(&source), graph.nodes_lookup.get(&target)) {
+            (Some(source), Some(target)) => self.graph(source.clone(), target.clone(), graph),
+            _ => Graph::from(Vec::new())
+        };
+    }
+
+    pub fn in_grid(&self, source: (usize, usize), target: (usize, usize), grid: &Grid, directions: &[Direction]) -> Graph {
+        if grid.outside(source) || grid.outside(target) {
+            return Graph::from(Vec::new());
+        }
+
+        return self.grid(source, target, grid, directions);
+    }
+
+    // A weight increase only affects paths using the edge, a decrease may create a shorter path for any query.
+    pub fn edge_changed(&self, edge_index: usize, previous: f32, weight: f32) {
+        let mut state = self.state.borrow_mut();
+
+        state.entries.retain(|key, entry| match key {
+            CacheKey::Grid(..) => true,
+            CacheKey::Graph(..) => weight > previous
+                && !entry.edges.iter().any(|edge| edge.index() == edge_index)
+        });
+    }
+
+    // Same as for edges, entering a cell costs the cell's cost.
+    pub fn cells_changed(&self, changes: &[CellChange]) {
+        let mut state = self.state.borrow_mut();
+
+        if changes.iter().any(|change| change.cost < change.previous) {
+            state.entries.retain(|key, _| matches!(key, CacheKey::Graph(..)));
+            return;
+        }
+
+        state.entries.retain(|key, entry| match key {
+            CacheKey::Graph(..) => true,
+            CacheKey::Grid(..) => !entry.edges.iter()
+                .any(|edge| changes.iter().any(|change| change.node_id == edge.destination))
+        });
+    }
+
+    fn sync(&self, grid: &Grid) {
+        let seen = self.state.borrow().grid_version;
+
+        if let Some(version) = seen {
+            match grid.changes_since(version) {
+                None => self.state.borrow_mut().entries.retain(|key, _| matches!(key, CacheKey::Graph(..))),
+                Some(changes) => self.cells_changed(changes),
+            }
+        }
+
+        self.state.borrow_mut().grid_version = Some(grid.version());
+    }
+
+    fn cached(&self, key: CacheKey, search: &dyn Fn() -> Graph) -> Graph {
+        {
+            let mut state = self.state.borrow_mut();
+            state.tick += 1;
+            let tick = state.tick;
+
+            if let Some(entry) = state.entries.get_mut(&key) {
+                entry.last_used = tick;
+                let edges = entry.edges.clone();
+                state.hits += 1;
+                return Graph::from(edges);
+            }
+            state.misses += 1;
+        }
+
+        let path = search();
+        let mut state = self.state.borrow_mut();
+
+        if self.capacity == 0 {
+            return path;
+        }
+
+        if state.entries.len() >= self.capacity {
+            let least_recent = state.entries.iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| *key)
+                .unwrap();
+            state.entries.remove(&least_recent);
+        }
+
+        let last_used = state.tick;
+        state.entries.insert(key, CacheEntry { edges: path.edges.clone(), last_used });
+        return path;
+    }
+}
+
+fn direction_mask(directions: &[Direction]) -> u8 {
+    return directions.iter().fold(0, |mask, direction| mask | match direction {
+        Direction::Up => 1,
+        Direction::Down => 2,
+        Direction::Left => 4,
+        Direction::Right => 8,
+        Direction::UpLeft => 16,
+        Direction::UpRight => 32,
+        Direction::DownLeft => 64,
+        Direction::DownRight => 128,
+    });
+}
+
+impl PathFinding for PathCache {
+    fn graph(&self, source: Node, target: Node, graph: &Graph) -> Graph {
+        let key = CacheKey::Graph(source.id, target.id);
+        return self.cached(key, &|| self.path_finding.graph(source.clone(), target.clone(), graph));
+    }
+
+    fn graph_with(&self, source: Node, target: Node, graph: &Graph, options: &QueryOptions) -> Graph {
+        return self.path_finding.graph_with(source, target, graph, options);
+    }
+
+    fn grid(&self, source: (usize, usize), target: (usize, usize), grid: &Grid, directions: &[Direction]) -> Graph {
+        self.sync(grid);
+
+        let key = CacheKey::Grid(grid.node_id(source), grid.node_id(target), direction_mask(directions));
+        return self.cached(key, &|| self.path_finding.grid(source, target, grid, directions));
+    }
+}
+
+
+#[cfg(test)]
+fn graph() -> Graph {
+    return Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 1.0),
+        Edge::from(1, 1, 2, 1.0),
+        Edge::from(2, 0, 2, 3.0),
+        Edge::from(3, 2, 3, 1.0),
+    ]));
+}
+
+#[cfg(test)]
+fn grid() -> Grid {
+    return Grid::from(&[
+        &[1.0, 1.0, 1.0],
+        &[1.0, 1.0, 1.0],
+        &[1.0, 1.0, 1.0],
+    ]);
+}
+
+#[cfg(test)]
+fn straight() -> Vec<Direction> {
+    return vec![Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+}
+
+#[test]
+fn cache_should_return_memoized_path() {
+    let cache = PathCache::from(Box::new(Dijkstra {}), 10);
+    let graph = graph();
+
+    let first = cache.in_graph(0, 3, &graph);
+    let second = cache.in_graph(0, 3, &graph);
+
+    assert!(first.edges == second.edges);
+    assert_eq!(1, cache.hits());
+    assert_eq!(1, cache.misses());
+    assert_eq!(1, cache.len());
+}
+
+#[test]
+fn cache_should_skip_queries_with_options() {
+    let cache = PathCache::from(Box::new(Dijkstra {}), 10);
+    let graph = graph();
+    let options = QueryOptions { blocked_edges: std::collections::HashSet::from([1]), ..QueryOptions::new() };
+
+    let constrained = cache.graph_with(graph.nodes_lookup[&0].clone(), graph.nodes_lookup[&2].clone(), &graph, &options);
+    let unconstrained = cache.in_graph(0, 2, &graph);
+
+    assert_eq!(1, constrained.edges.len());
+    assert_eq!(2, unconstrained.edges.len());
+    assert_eq!(1, cache.len());
+}
+
+#[test]
+fn cache_should_evict_least_recently_used_path() {
+    let cache = PathCache::from(Box::new(Dijkstra {}), 2);
+    let graph = graph();
+
+    cache.in_graph(0, 1, &graph);
+    cache.in_graph(0, 2, &graph);
+    cache.in_graph(0, 1, &graph);
+    cache.in_graph(0, 3, &graph);
+    cache.in_graph(0, 1, &graph);
+    cache.in_graph(0, 2, &graph);
+
+    assert_eq!(2, cache.len());
+    assert_eq!(2, cache.hits());
+    assert_eq!(4, cache.misses());
+}
+
+#[test]
+fn cache_should_distinguish_directions() {
+    let cache = PathCache::from(Box::new(Dijkstra {}), 10);
+    let grid = grid();
+    let mut all = straight();
+    all.push(Direction::DownRight);
+
+    let straight_path = cache.in_grid((0, 0), (2, 2), &grid, &straight());
+    let diagonal_path = cache.in_grid((0, 0), (2, 2), &grid, &all);
+
+    assert_eq!(4, straight_path.edges.len());
+    assert_eq!(2, diagonal_path.edges.len());
+    assert_eq!(0, cache.hits());
+}
+
+#[test]
+fn edge_increase_should_only_invalidate_paths_using_edge() {
+    let cache = PathCache::from(Box::new(Dijkstra {}), 10);
+    let graph = graph();
+    cache.in_graph(0, 1, &graph);
+    cache.in_graph(2, 3, &graph);
+
+    cache.edge_changed(0, 1.0, 5.0);
+    assert_eq!(1, cache.len());
+
+    cache.edge_changed(3, 1.0, 0.5);
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn grid_changes_should_invalidate_affected_paths() {
+    let cache = PathCache::from(Box::new(Dijkstra {}), 10);
+    let mut grid = grid();
+    let path = cache.in_grid((0, 0), (0, 2), &grid, &straight());
+    cache.in_grid((2, 0), (2, 2), &grid, &straight());
+
+    grid.block((0, 1));
+    let replanned = cache.in_grid((0, 0), (0, 2), &grid, &straight());
+    cache.in_grid((2, 0), (2, 2), &grid, &straight());
+
+    assert_eq!(2, path.edges.len());
+    assert_eq!(4, replanned.edges.len());
+    assert_eq!(1, cache.hits());
+
+    grid.unblock((0, 1), 1.0);
+    let restored = cache.in_grid((0, 0), (0, 2), &grid, &straight());
+
+    assert_eq!(2, restored.edges.len());
+    assert_eq!(1, cache.hits());
+}
+
+#[test]
+fn cleared_grid_changes_should_invalidate_all_paths() {
+    let cache = PathCache::from(Box::new(Dijkstra {}), 10);
+    let mut grid = grid();
+    cache.in_grid((2, 0), (2, 2), &grid, &straight());
+
+    grid.set_cost((0, 0), 2.0);
+    grid.clear_changes();
+    cache.in_grid((2, 0), (2, 2), &grid, &straight());
+
+    assert_eq!(0, cache.hits());
+}
@@ -0,0 +1,339 @@
+use std::collections::HashMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+use crate::graph::Edge;
+use crate::graph::Graph;
+use crate::grid::{CostModel, Direction, Grid};
+#[cfg(test)]
+use crate::search::cost::INFINITY;
+use crate::spanning::NodeIndex;
+use crate::union_find::UnionFind;
+
+pub fn connected_components(graph: &Graph) -> HashMap<usize, usize> {
+    let node_index = NodeIndex::from(graph);
+    let mut union_find = UnionFind::from(node_index.ids.len());
+
+    for edge in &graph.edges {
+        union_find.unify(node_index.index(edge.source), node_index.index(edge.destination));
+    }
+
+    let roots = node_index.ids.iter().enumerate()
+        .map(|(index, node_id)| (*node_id, union_find.find(index)))
+        .collect();
+
+    return normalize(roots);
+}
+
+pub fn strongly_connected_components(graph: &Graph) -> HashMap<usize, usize> {
+    return tarjan(graph);
+}
+
+pub fn component_count(components: &HashMap<usize, usize>) -> usize {
+    return components.values().max().map_or(0, |max| max + 1);
+}
+
+pub fn tarjan(graph: &Graph) -> HashMap<usize, usize> {
+    let node_index = NodeIndex::from(graph);
+    let adjacency = adjacency(graph, &node_index);
+    let node_count = node_index.ids.len();
+
+    let mut order: Vec<Option<usize>> = vec![None; node_count];
+    let mut low_link: Vec<usize> = vec![0; node_count];
+    let mut on_stack: Vec<bool> = vec![false; node_count];
+    let mut stack: Vec<usize> = Vec::new();
+    let mut components: Vec<usize> = vec![0; node_count];
+    let mut counter = 0;
+    let mut component = 0;
+
+    for start in 0..node_count {
+        if order[start].is_some() {
+            continue;
+        }
+
+        // (node, position of the next neighbour to visit)
+        let mut call_stack: Vec<(usize, usize)> = vec![(start, 0)];
+        order[start] = Some(counter);
+        low_link[start] = counter;
+        counter += 1;
+        stack.push(start);
+        on_stack[start] = true;
+
+        while let Some((node, next)) = call_stack.pop() {
+            if next < adjacency[node].len() {
+                call_stack.push((node, next + 1));
+                let neighbour = adjacency[node][next];
+
+                match order[neighbour] {
+                    None => {
+                        order[neighbour] = Some(counter);
+                        low_link[neighbour] = counter;
+                        counter += 1;
+                        stack.push(neighbour);
+                        on_stack[neighbour] = true;
+                        call_stack.push((neighbour, 0));
+                    }
+                    Some(neighbour_order) => {
+                        if on_stack[neighbour] {
+                            low_link[node] = low_link[node].min(neighbour_order);
+                        }
+                    }
+                }
+                continue;
+            }
+
+            if let Some((parent, _)) = call_stack.last() {
+                low_link[*parent] = low_link[*parent].min(low_link[node]);
+            }
+
+            if Some(low_link[node]) == order[node] {
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    components[member] = component;
+                    if member == node {
+                        break;
+                    }
+                }
+                component += 1;
+            }
+        }
+    }
+
+    return normalize(node_index.ids.iter().enumerate()
+        .map(|(index, node_id)| (*node_id, components[index]))
+        .collect());
+}
+
+pub fn kosaraju(graph: &Graph) -> HashMap<usize, usize> {
+    let node_index = NodeIndex::from(graph);
+    let adjacency = adjacency(graph, &node_index);
+    let reversed = adjacency_reversed(&adjacency);
+    let node_count = node_index.ids.len();
+
+    let mut visited: Vec<bool> = vec![false; node_count];
+    let mut finished: Vec<usize> = Vec::with_capacity(node_count);
+
+    for start in 0..node_count {
+        if visited[start] {
+            continue;
+        }
+
+        visited[start] = true;
+        let mut call_stack: Vec<(usize, usize)> = vec![(start, 0)];
+
+        while let Some((node, next)) = call_stack.pop() {
+            if next < adjacency[node].len() {
+                call_stack.push((node, next + 1));
+                let neighbour = adjacency[node][next];
+
+                if !visited[neighbour] {
+                    visited[neighbour] = true;
+                    call_stack.push((neighbour, 0));
+                }
+            } else {
+                finished.push(node);
+            }
+        }
+    }
+
+    let mut components: Vec<Option<usize>> = vec![None; node_count];
+    let mut component = 0;
+
+    for start in finished.into_iter().rev() {
+        if components[start].is_some() {
+            continue;
+        }
+
+        components[start] = Some(component);
+        let mut stack = vec![start];
+
+        while let Some(node) = stack.pop() {
+            for neighbour in &reversed[node] {
+                if components[*neighbour].is_none() {
+                    components[*neighbour] = Some(component);
+                    stack.push(*neighbour);
+                }
+            }
+        }
+
+        component += 1;
+    }
+
+    return normalize(node_index.ids.iter().enumerate()
+        .map(|(index, node_id)| (*node_id, components[index].unwrap()))
+        .collect());
+}
+
+fn adjacency(graph: &Graph, node_index: &NodeIndex) -> Vec<Vec<usize>> {
+    let mut adjacency = vec![Vec::new(); node_index.ids.len()];
+
+    for edge in &graph.edges {
+        adjacency[node_index.index(edge.source)].push(node_index.index(edge.destination));
+    }
+
+    return adjacency;
+}
+
+fn adjacency_reversed(adjacency: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut reversed = vec![Vec::new(); adjacency.len()];
+
+    for (node, neighbours) in adjacency.iter().enumerate() {
+        for neighbour in neighbours {
+            reversed[*neighbour].push(node);
+        }
+    }
+
+    return reversed;
+}
+
+// Renumbers components as 0, 1, 2, ... in order of their smallest node id.
+fn normalize(components: HashMap<usize, usize>) -> HashMap<usize, usize> {
+    let mut node_ids: Vec<usize> = components.keys().cloned().collect();
+    node_ids.sort();
+
+    let mut renumbered: HashMap<usize, usize> = HashMap::new();
+    let mut result: HashMap<usize, usize> = HashMap::new();
+
+    for node_id in node_ids {
+        let component = components[&node_id];
+        let next = renumbered.len();
+        let id = *renumbered.entry(component).or_insert(next);
+        result.insert(node_id, id);
+    }
+
+    return result;
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GridRegions {
+    regions: Vec<Option<usize>>,
+    pub count: usize,
+}
+
+impl GridRegions {
+    pub fn from(grid: &Grid, directions: &[Direction]) -> GridRegions {
+        let mut union_find = UnionFind::from(grid.size);
+        for edge in Graph::from_grid(grid, directions, CostModel::Destination).edges {
+            union_find.unify(edge.source, edge.destination);
+        }
+
+        let mut renumbered: HashMap<usize, usize> = HashMap::new();
+        let regions = (0..grid.size).map(|node_id| {
+            if !grid.walkable(node_id) {
+                return None;
+            }
+
+            let next = renumbered.len();
+            return Some(*renumbered.entry(union_find.find(node_id)).or_insert(next));
+        }).collect();
+
+        return GridRegions {
+            regions,
+            count: renumbered.len(),
+        };
+    }
+
+    pub fn region(&self, node_id: usize) -> Option<usize> {
+        return self.regions.get(node_id).cloned().flatten();
+    }
+
+    pub fn reachable(&self, source: usize, target: usize) -> bool {
+        return match (self.region(source), self.region(target)) {
+            (Some(source_region), Some(target_region)) => source_region == target_region,
+            _ => false
+        };
+    }
+}
+
+
+#[cfg(test)]
+fn directed_graph() -> Graph {
+    return Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 1.0),
+        Edge::from(1, 1, 2, 1.0),
+        Edge::from(2, 2, 0, 1.0),
+        Edge::from(3, 2, 3, 1.0),
+        Edge::from(4, 3, 4, 1.0),
+        Edge::from(5, 4, 3, 1.0),
+        Edge::from(6, 4, 5, 1.0),
+        Edge::from(7, 10, 11, 1.0),
+    ]));
+}
+
+#[cfg(test)]
+fn four_directions() -> [Direction; 4] {
+    return [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+}
+
+#[test]
+fn connected_components_should_map_nodes_to_components() {
+    let components = connected_components(&directed_graph());
+
+    assert_eq!(2, component_count(&components));
+    assert_eq!(0, components[&0]);
+    assert_eq!(0, components[&5]);
+    assert_eq!(1, components[&10]);
+    assert_eq!(1, components[&11]);
+}
+
+#[test]
+fn connected_components_of_empty_graph_should_be_empty() {
+    let components = connected_components(&Graph::from(Vec::new()));
+
+    assert!(components.is_empty());
+    assert_eq!(0, component_count(&components));
+}
+
+#[test]
+fn tarjan_should_find_strongly_connected_components() {
+    let components = tarjan(&directed_graph());
+
+    assert_eq!(5, component_count(&components));
+    assert_eq!(components[&0], components[&1]);
+    assert_eq!(components[&1], components[&2]);
+    assert_eq!(components[&3], components[&4]);
+    assert_ne!(components[&2], components[&3]);
+    assert_ne!(components[&4], components[&5]);
+    assert_ne!(components[&10], components[&11]);
+}
+
+#[test]
+fn kosaraju_should_be_equal_to_tarjan() {
+    let graph = directed_graph();
+
+    assert_eq!(tarjan(&graph), kosaraju(&graph));
+    assert_eq!(strongly_connected_components(&graph), kosaraju(&graph));
+}
+
+#[test]
+fn grid_regions_should_separate_walkable_areas() {
+    let grid = Grid::from(&[
+        &[0.0, 0.0, INFINITY, 0.0],
+        &[0.0, 0.0, INFINITY, 0.0],
+        &[INFINITY, INFINITY, INFINITY, 0.0],
+    ]);
+    let regions = GridRegions::from(&grid, &four_directions());
+
+    assert_eq!(2, regions.count);
+    assert!(regions.reachable(grid.node_id((0, 0)), grid.node_id((1, 1))));
+    assert!(!regions.reachable(grid.node_id((0, 0)), grid.node_id((2, 3))));
+    assert!(!regions.reachable(grid.node_id((0, 0)), grid.node_id((0, 2))));
+    assert_eq!(None, regions.region(grid.node_id((2, 0))));
+}
+
+#[test]
+fn grid_regions_should_respect_diagonal_directions() {
+    let grid = Grid::from(&[
+        &[0.0, INFINITY],
+        &[INFINITY, 0.0],
+    ]);
+
+    let straight = GridRegions::from(&grid, &four_directions());
+    let diagonal = GridRegions::from(&grid, &[Direction::DownRight, Direction::UpLeft]);
+
+    assert_eq!(2, straight.count);
+    assert_eq!(1, diagonal.count);
+    assert!(diagonal.reachable(0, 3));
+}
@@ -0,0 +1,362 @@
+use std::collections::{HashMap, HashSet};
+
+use ordered_float::NotNan;
+use priority_queue::DoublePriorityQueue;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::graph::Graph;
+#[cfg(test)]
+use crate::graph::Edge;
+use crate::grid::{CostModel, Direction, Grid};
+use crate::multi_agent::{Agent, Conflict, conflicts, MultiAgentPlan, State, TimedPath, timed_path, walk_back};
+#[cfg(test)]
+use crate::multi_agent::{corridor, first_conflict, straight};
+use crate::search::adjacency::{adjacency, distances, Adjacency};
+
+const EPSILON: f32 = 1e-4;
+
+// Conflict-Based Search. With a suboptimality above one, this is Enhanced CBS (ECBS): both levels pick, among the
+// candidates within the bound of the best lower bound, the one with the fewest conflicts. The sum of costs is then at
+// most the suboptimality times the optimal sum of costs.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ConflictBasedSearch {
+    pub suboptimality: f32,
+    pub max_expansions: usize,
+}
+
+#[derive(Clone, Default)]
+struct Constraints {
+    vertices: HashSet<(usize, usize)>,
+    moves: HashSet<(usize, usize, usize)>,
+}
+
+struct ConstraintNode {
+    constraints: Vec<Constraints>,
+    paths: Vec<TimedPath>,
+    bounds: Vec<f32>,
+    conflicts: usize,
+}
+
+impl ConstraintNode {
+    fn cost(&self) -> f32 {
+        return self.paths.iter().map(|path| path.cost).sum();
+    }
+
+    fn bound(&self) -> f32 {
+        return self.bounds.iter().sum();
+    }
+}
+
+struct Problem<'a> {
+    adjacency: Adjacency,
+    heuristics: Vec<HashMap<usize, f32>>,
+    sources: Vec<usize>,
+    targets: Vec<usize>,
+    node_count: usize,
+    wait_cost: &'a dyn Fn(usize) -> f32,
+    step_cost: &'a dyn Fn(usize, usize) -> f32,
+}
+
+impl ConflictBasedSearch {
+    pub fn optimal() -> ConflictBasedSearch {
+        return ConflictBasedSearch { suboptimality: 1.0, max_expansions: 10_000 };
+    }
+
+    // Waiting costs the cost of the current cell.
+    pub fn grid(&self, grid: &Grid, directions: &[Direction], agents: &[Agent]) -> Option<MultiAgentPlan> {
+        let (forward, backward) = adjacency(&Graph::from_grid(grid, directions, CostModel::Destination));
+        let targets: Vec<usize> = agents.iter().map(|agent| grid.node_id(agent.target)).collect();
+
+        return self.solve(&Problem {
+            adjacency: forward,
+            heuristics: targets.iter().map(|target| distances(*target, &backward)).collect(),
+            sources: agents.iter().map(|agent| grid.node_id(agent.source)).collect(),
+            targets,
+            node_count: grid.size,
+            wait_cost: &|node_id| grid.cost(node_id),
+            step_cost: &|from, to| if from == to { grid.cost(from) } else { grid.cost(to) },
+        });
+    }
+
+    // Agents are given as (source, target) node ids, waiting at a node costs the wait cost.
+    pub fn graph(&self, graph: &Graph, agents: &[(usize, usize)], wait_cost: f32) -> Option<MultiAgentPlan> {
+        let (forward, backward) = adjacency(graph);
+        let targets: Vec<usize> = agents.iter().map(|agent| agent.1).collect();
+        let cheapest = |from: usize, to: usize| forward.get(&from).unwrap_or(&Vec::new()).iter()
+            .filter(|(neighbour, _)| *neighbour == to)
+            .map(|(_, weight)| *weight)
+            .fold(f32::INFINITY, f32::min);
+
+        return self.solve(&Problem {
+            adjacency: forward.clone(),
+            heuristics: targets.iter().map(|target| distances(*target, &backward)).collect(),
+            sources: agents.iter().map(|agent| agent.0).collect(),
+            targets,
+            node_count: graph.node_count,
+            wait_cost: &|_| wait_cost,
+            step_cost: &|from, to| if from == to { wait_cost } else { cheapest(from, to) },
+        });
+    }
+
+    fn solve(&self, problem: &Problem) -> Option<MultiAgentPlan> {
+        let agent_count = problem.sources.len();
+        let mut root = ConstraintNode {
+            constraints: vec![Constraints::default(); agent_count],
+            paths: Vec::new(),
+            bounds: Vec::new(),
+            conflicts: 0,
+        };
+
+        for agent in 0..agent_count {
+            let (path, bound) = self.low_level(problem, agent, &root.constraints[agent], &root.paths)?;
+            root.paths.push(path);
+            root.bounds.push(bound);
+        }
+        root.conflicts = conflicts(&root.paths).len();
+
+        let mut open = vec![root];
+        let mut expansions = 0;
+
+        while !open.is_empty() && expansions < self.max_expansions {
+            expansions += 1;
+            let node = open.swap_remove(self.select(&open));
+
+            let conflict = match conflicts(&node.paths).first() {
+                None => return Some(MultiAgentPlan::from(node.paths)),
+                Some(conflict) => *conflict
+            };
+
+            let split = match conflict {
+                Conflict::Vertex { agents, node_id, time } => [
+                    (agents.0, Some((node_id, time)), None),
+                    (agents.1, Some((node_id, time)), None),
+                ],
+                Conflict::Edge { agents, from, to, time } => [
+                    (agents.0, None, Some((from, to, time))),
+                    (agents.1, None, Some((to, from, time))),
+                ],
+            };
+
+            for (agent, vertex, step) in split {
+                let mut constraints = node.constraints.clone();
+                constraints[agent].vertices.extend(vertex);
+                constraints[agent].moves.extend(step);
+
+                if let Some((path, bound)) = self.low_level(problem, agent, &constraints[agent], &node.paths) {
+                    let mut paths = node.paths.clone();
+                    let mut bounds = node.bounds.clone();
+                    paths[agent] = path;
+                    bounds[agent] = bound;
+
+                    let conflicts = conflicts(&paths).len();
+                    open.push(ConstraintNode { constraints, paths, bounds, conflicts });
+                }
+            }
+        }
+
+        return None;
+    }
+
+    // Among the nodes within the bound, the one with the fewest conflicts, then the lowest cost.
+    fn select(&self, open: &[ConstraintNode]) -> usize {
+        let lowest = open.iter().map(|node| node.bound()).fold(f32::INFINITY, f32::min);
+        let limit = lowest * self.suboptimality.max(1.0) + EPSILON;
+
+        // The node with the lowest bound is always within the bound, as the low level keeps paths within it.
+        return (0..open.len())
+            .filter(|index| open[*index].cost() <= limit)
+            .min_by(|index1, index2| open[*index1].conflicts.cmp(&open[*index2].conflicts)
+                .then(open[*index1].cost().total_cmp(&open[*index2].cost())))
+            .unwrap_or_else(|| (0..open.len())
+                .min_by(|index1, index2| open[*index1].bound().total_cmp(&open[*index2].bound()))
+                .unwrap());
+    }
+
+    // Space-time search for one agent, respecting its constraints. Returns the path and a lower bound for the cost
+    // of the agent's optimal path.
+    fn low_level(&self,
+                 problem: &Problem,
+                 agent: usize,
+                 constraints: &Constraints,
+                 others: &[TimedPath]) -> Option<(TimedPath, f32)> {
+        let (source, target) = (problem.sources[agent], problem.targets[agent]);
+        let heuristic = &problem.heuristics[agent];
+
+        let last_constraint = constraints.vertices.iter().map(|(_, time)| *time)
+            .chain(constraints.moves.iter().map(|(_, _, time)| *time))
+            .max();
+        let earliest = constraints.vertices.iter()
+            .filter(|(node_id, _)| *node_id == target)
+            .map(|(_, time)| time + 1)
+            .max()
+            .unwrap_or(0);
+        let max_time = last_constraint.map_or(0, |time| time + 1) + problem.node_count;
+
+        if constraints.vertices.contains(&(source, 0)) {
+            return None;
+        }
+
+        let mut states = vec![State { node_id: source, time: 0, cost: 0.0, conflicts: 0, previous: None }];
+        let mut best: HashMap<(usize, usize), f32> = HashMap::from([((source, 0), 0.0)]);
+        let mut open: DoublePriorityQueue<usize, NotNan<f32>> = DoublePriorityQueue::new();
+        open.push(0, NotNan::new(*heuristic.get(&source)?).unwrap());
+
+        while let Some((_, lowest)) = open.peek_min() {
+            let lowest = lowest.into_inner();
+            let current = self.focal(&open, &states, lowest);
+            open.remove(&current);
+
+            let state = &states[current];
+            let (node_id, time, cost, conflict_count) = (state.node_id, state.time, state.cost, state.conflicts);
+
+            if best.get(&(node_id, time)).is_some_and(|known| *known < cost) {
+                continue;
+            }
+
+            if node_id == target && time >= earliest {
+                let steps = walk_back(&states, current);
+                return Some((timed_path(steps, target, problem.step_cost), lowest));
+            }
+
+            if time >= max_time {
+                continue;
+            }
+
+            let waiting = (node_id, (problem.wait_cost)(node_id));
+            let moves = problem.adjacency.get(&node_id).map_or(&[][..], |neighbours| &neighbours[..]);
+
+            for (next, step_cost) in moves.iter().cloned().chain([waiting]) {
+                let estimate = match heuristic.get(&next) {
+                    None => continue,
+                    Some(estimate) => *estimate
+                };
+
+                if constraints.vertices.contains(&(next, time + 1)) || constraints.moves.contains(&(node_id, next, time)) {
+                    continue;
+                }
+
+                let next_cost = cost + step_cost;
+                if best.get(&(next, time + 1)).is_some_and(|known| *known <= next_cost) {
+                    continue;
+                }
+
+                let collisions = (0..others.len())
+                    .filter(|other| *other != agent)
+                    .filter(|other| collides(&others[*other], node_id, next, time))
+                    .count();
+
+                best.insert((next, time + 1), next_cost);
+                open.push(states.len(), NotNan::new(next_cost + estimate).unwrap());
+                states.push(State {
+                    node_id: next,
+                    time: time + 1,
+                    cost: next_cost,
+                    conflicts: conflict_count + collisions,
+                    previous: Some(current),
+                });
+            }
+        }
+
+        return None;
+    }
+
+    fn focal(&self, open: &DoublePriorityQueue<usize, NotNan<f32>>, states: &[State], lowest: f32) -> usize {
+        if self.suboptimality <= 1.0 {
+            return *open.peek_min().unwrap().0;
+        }
+
+        let limit = lowest * self.suboptimality + EPSILON;
+        return *open.iter()
+            .filter(|(_, estimate)| estimate.into_inner() <= limit)
+            .min_by(|(state1, estimate1), (state2, estimate2)| states[**state1].conflicts
+                .cmp(&states[**state2].conflicts)
+                .then(estimate1.cmp(estimate2)))
+            .unwrap().0;
+    }
+}
+
+fn collides(other: &TimedPath, from: usize, to: usize, time: usize) -> bool {
+    return other.at(time + 1) == to || (from != to && other.at(time) == to && other.at(time + 1) == from);
+}
+
+
+#[cfg(test)]
+fn branching_graph() -> Graph {
+    // a line 0 - 1 - 2 with a dead end 3 at node 1
+    return Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 1.0),
+        Edge::from(1, 1, 0, 1.0),
+        Edge::from(2, 1, 2, 1.0),
+        Edge::from(3, 2, 1, 1.0),
+        Edge::from(4, 1, 3, 1.0),
+        Edge::from(5, 3, 1, 1.0),
+    ]));
+}
+
+#[test]
+fn cbs_should_plan_independent_agents_optimally() {
+    let grid = Grid::from(&[
+        &[1.0, 1.0, 1.0],
+        &[1.0, 1.0, 1.0],
+    ]);
+    let agents = [
+        Agent { source: (0, 0), target: (0, 2) },
+        Agent { source: (1, 2), target: (1, 0) },
+    ];
+
+    let plan = ConflictBasedSearch::optimal().grid(&grid, &straight(), &agents).unwrap();
+
+    assert_eq!(4.0, plan.sum_of_costs);
+    assert_eq!(None, first_conflict(&plan.paths));
+}
+
+#[test]
+fn cbs_should_resolve_swap_in_corridor_optimally() {
+    let agents = [
+        Agent { source: (0, 0), target: (0, 4) },
+        Agent { source: (0, 4), target: (0, 0) },
+    ];
+
+    let plan = ConflictBasedSearch::optimal().grid(&corridor(), &straight(), &agents).unwrap();
+
+    assert_eq!(None, first_conflict(&plan.paths));
+    assert_eq!(11.0, plan.sum_of_costs);
+    assert_eq!(4, *plan.paths[0].steps.last().unwrap());
+    assert_eq!(0, *plan.paths[1].steps.last().unwrap());
+}
+
+#[test]
+fn ecbs_should_stay_within_suboptimality_bound() {
+    let agents = [
+        Agent { source: (0, 0), target: (0, 4) },
+        Agent { source: (0, 4), target: (0, 0) },
+    ];
+    let ecbs = ConflictBasedSearch { suboptimality: 1.5, max_expansions: 10_000 };
+
+    let plan = ecbs.grid(&corridor(), &straight(), &agents).unwrap();
+
+    assert_eq!(None, first_conflict(&plan.paths));
+    assert!(plan.sum_of_costs <= 1.5 * 11.0);
+}
+
+#[test]
+fn cbs_should_resolve_swap_in_graph() {
+    let plan = ConflictBasedSearch::optimal().graph(&branching_graph(), &[(0, 2), (2, 0)], 1.0).unwrap();
+
+    assert_eq!(None, first_conflict(&plan.paths));
+    assert_eq!(7.0, plan.sum_of_costs);
+    assert_eq!(vec![3.0, 4.0], {
+        let mut costs: Vec<f32> = plan.paths.iter().map(|path| path.cost).collect();
+        costs.sort_by(|cost1, cost2| cost1.total_cmp(cost2));
+        costs
+    });
+}
+
+#[test]
+fn cbs_should_fail_for_shared_target() {
+    let cbs = ConflictBasedSearch { suboptimality: 1.0, max_expansions: 50 };
+
+    assert!(cbs.graph(&branching_graph(), &[(0, 2), (3, 2)], 1.0).is_none());
+}
@@ -0,0 +1,442 @@
+use std::collections::HashMap;
+
+use ordered_float::NotNan;
+use priority_queue::DoublePriorityQueue;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+use crate::graph::Edge;
+use crate::graph::Graph;
+#[cfg(test)]
+use crate::path::in_graph;
+use crate::path::Traced;
+#[cfg(test)]
+use crate::search::Dijkstra;
+use crate::search::expansions::Expansions;
+use crate::spanning::NodeIndex;
+
+// Witness searches give up after settling this many nodes, and a shortcut is added instead.
+const WITNESS_SETTLE_LIMIT: usize = 500;
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+enum ArcKind {
+    Edge(usize),
+    Shortcut(usize, usize),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Arc {
+    source: usize,
+    destination: usize,
+    weight: f32,
+    kind: ArcKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ContractionHierarchy {
+    lookup: HashMap<usize, usize>,
+    ranks: Vec<usize>,
+    arcs: Vec<Arc>,
+    upward: Vec<Vec<usize>>,
+    downward: Vec<Vec<usize>>,
+}
+
+struct Contraction {
+    arcs: Vec<Arc>,
+    outgoing: Vec<HashMap<usize, usize>>,
+    incoming: Vec<HashMap<usize, usize>>,
+    contracted_neighbours: Vec<i64>,
+}
+
+impl Contraction {
+    fn from(graph: &Graph, node_index: &NodeIndex) -> Contraction {
+        let node_count = node_index.ids.len();
+        let mut contraction = Contraction {
+            arcs: Vec::with_capacity(graph.edges.len()),
+            outgoing: vec![HashMap::new(); node_count],
+            incoming: vec![HashMap::new(); node_count],
+            contracted_neighbours: vec![0; node_count],
+        };
+
+        for edge in &graph.edges {
+            let (source, destination) = (node_index.index(edge.source), node_index.index(edge.destination));
+            contraction.add_arc(Arc { source, destination, weight: edge.weight, kind: ArcKind::Edge(edge.index()) });
+        }
+
+        return contraction;
+    }
+
+    // Only the cheapest arc between two nodes takes part in the contraction, every arc is kept for the query.
+    fn add_arc(&mut self, arc: Arc) {
+        let (source, destination, weight) = (arc.source, arc.destination, arc.weight);
+        let id = self.arcs.len();
+        self.arcs.push(arc);
+
+        if source == destination {
+            return;
+        }
+
+        let cheaper = self.outgoing[source].get(&destination)
+            .is_none_or(|existing| self.arcs[*existing].weight > weight);
+
+        if cheaper {
+            self.outgoing[source].insert(destination, id);
+            self.incoming[destination].insert(source, id);
+        }
+    }
+
+    fn shortcuts(&self, node: usize) -> Vec<(usize, usize)> {
+        let mut shortcuts = Vec::new();
+        let max_outgoing = self.outgoing[node].values()
+            .map(|arc| self.arcs[*arc].weight)
+            .fold(0.0, f32::max);
+
+        for (predecessor, arc_in) in &self.incoming[node] {
+            let weight_in = self.arcs[*arc_in].weight;
+            let distances = self.witness_search(*predecessor, node, weight_in + max_outgoing);
+
+            for (successor, arc_out) in &self.outgoing[node] {
+                if successor == predecessor {
+                    continue;
+                }
+
+                let weight = weight_in + self.arcs[*arc_out].weight;
+                if distances.get(successor).is_none_or(|distance| *distance > weight) {
+                    shortcuts.push((*arc_in, *arc_out));
+                }
+            }
+        }
+
+        return shortcuts;
+    }
+
+    fn witness_search(&self, source: usize, excluded: usize, limit: f32) -> HashMap<usize, f32> {
+        let mut distances: HashMap<usize, f32> = HashMap::new();
+        let mut queue: DoublePriorityQueue<usize, NotNan<f32>> = DoublePriorityQueue::new();
+        queue.push(source, NotNan::new(0.0).unwrap());
+
+        while let Some((node, distance)) = queue.pop_min() {
+            if distance.into_inner() > limit || distances.len() >= WITNESS_SETTLE_LIMIT {
+                break;
+            }
+            distances.insert(node, distance.into_inner());
+
+            for (neighbour, arc) in &self.outgoing[node] {
+                if *neighbour == excluded || distances.contains_key(neighbour) {
+                    continue;
+                }
+
+                let cost = distance + self.arcs[*arc].weight;
+                if queue.get_priority(neighbour).is_none_or(|queued| *queued > cost) {
+                    queue.push(*neighbour, cost);
+                }
+            }
+        }
+
+        return distances;
+    }
+
+    fn priority(&self, node: usize) -> i64 {
+        let degree = (self.incoming[node].len() + self.outgoing[node].len()) as i64;
+        return self.shortcuts(node).len() as i64 - degree + self.contracted_neighbours[node];
+    }
+
+    fn contract(&mut self, node: usize) {
+        for (arc_in, arc_out) in self.shortcuts(node) {
+            let (source, destination) = (self.arcs[arc_in].source, self.arcs[arc_out].destination);
+            let weight = self.arcs[arc_in].weight + self.arcs[arc_out].weight;
+            self.add_arc(Arc { source, destination, weight, kind: ArcKind::Shortcut(arc_in, arc_out) });
+        }
+
+        let predecessors: Vec<usize> = self.incoming[node].keys().cloned().collect();
+        let successors: Vec<usize> = self.outgoing[node].keys().cloned().collect();
+
+        for predecessor in predecessors {
+            self.outgoing[predecessor].remove(&node);
+            self.contracted_neighbours[predecessor] += 1;
+        }
+
+        for successor in successors {
+            self.incoming[successor].remove(&node);
+            self.contracted_neighbours[successor] += 1;
+        }
+
+        self.incoming[node].clear();
+        self.outgoing[node].clear();
+    }
+}
+
+impl ContractionHierarchy {
+    pub fn from(graph: &Graph) -> ContractionHierarchy {
+        let node_index = NodeIndex::from(graph);
+        let node_count = node_index.ids.len();
+        let mut contraction = Contraction::from(graph, &node_index);
+
+        let mut queue: DoublePriorityQueue<usize, i64> = DoublePriorityQueue::new();
+        for node in 0..node_count {
+            queue.push(node, contraction.priority(node));
+        }
+
+        let mut ranks = vec![0; node_count];
+        let mut rank = 0;
+
+        // Priorities change as neighbours get contracted, they are updated lazily before contracting a node.
+        while let Some((node, _)) = queue.pop_min() {
+            let priority = contraction.priority(node);
+
+            if queue.peek_min().is_some_and(|(_, next)| *next < priority) {
+                queue.push(node, priority);
+                continue;
+            }
+
+            contraction.contract(node);
+            ranks[node] = rank;
+            rank += 1;
+        }
+
+        let mut upward = vec![Vec::new(); node_count];
+        let mut downward = vec![Vec::new(); node_count];
+
+        for (id, arc) in contraction.arcs.iter().enumerate() {
+            if ranks[arc.source] < ranks[arc.destination] {
+                upward[arc.source].push(id);
+            } else if ranks[arc.source] > ranks[arc.destination] {
+                downward[arc.destination].push(id);
+            }
+        }
+
+        return ContractionHierarchy {
+            lookup: node_index.lookup,
+            ranks,
+            arcs: contraction.arcs,
+            upward,
+            downward,
+        };
+    }
+
+    pub fn shortcut_count(&self) -> usize {
+        return self.arcs.iter().filter(|arc| matches!(arc.kind, ArcKind::Shortcut(_, _))).count();
+    }
+
+    pub fn rank(&self, node_id: usize) -> Option<usize> {
+        return self.lookup.get(&node_id).map(|node| self.ranks[*node]);
+    }
+
+    pub fn distance(&self, source: usize, target: usize) -> Option<f32> {
+        return self.query(source, target, &mut Expansions::off()).map(|(distance, _)| distance);
+    }
+
+    pub fn edge_indices(&self, source: usize, target: usize) -> Option<Vec<usize>> {
+        return self.query(source, target, &mut Expansions::off()).map(|(_, arcs)| self.unpack_all(&arcs));
+    }
+
+    pub fn path(&self, source: usize, target: usize, graph: &Graph) -> Graph {
+        let edges = self.edge_indices(source, target).unwrap_or_default().iter()
+            .map(|index| graph.edges_lookup[index].clone())
+            .collect();
+
+        return Graph::from(edges);
+    }
+
+    // Path together with the node ids settled by the forward search, followed by those of the backward search.
+    pub fn path_traced(&self, source: usize, target: usize, graph: &Graph) -> Traced {
+        let mut expansions = Expansions::on();
+        let indices = self.query(source, target, &mut expansions)
+            .map(|(_, arcs)| self.unpack_all(&arcs))
+            .unwrap_or_default();
+
+        let mut ids = vec![0; self.lookup.len()];
+        for (id, node) in &self.lookup {
+            ids[*node] = *id;
+        }
+
+        return Traced {
+            path: Graph::from(indices.iter().map(|index| graph.edges_lookup[index].clone()).collect::<Vec<_>>()),
+            expanded: expansions.into_nodes().map(|nodes| nodes.iter().map(|node| ids[*node]).collect()),
+        };
+    }
+
+    fn unpack_all(&self, arcs: &[usize]) -> Vec<usize> {
+        let mut indices = Vec::new();
+        for arc in arcs {
+            self.unpack(*arc, &mut indices);
+        }
+        return indices;
+    }
+
+    fn unpack(&self, arc: usize, indices: &mut Vec<usize>) {
+        match self.arcs[arc].kind {
+            ArcKind::Edge(index) => indices.push(index),
+            ArcKind::Shortcut(first, second) => {
+                self.unpack(first, indices);
+                self.unpack(second, indices);
+            }
+        }
+    }
+
+    // Bidirectional search, which only relaxes arcs towards higher ranked nodes from both sides.
+    fn query(&self, source: usize, target: usize, expansions: &mut Expansions) -> Option<(f32, Vec<usize>)> {
+        let source = *self.lookup.get(&source)?;
+        let target = *self.lookup.get(&target)?;
+
+        let forward = self.upward_search(source, &self.upward, |arc| arc.destination, expansions);
+        let backward = self.upward_search(target, &self.downward, |arc| arc.source, expansions);
+
+        let (meeting, distance) = forward.iter()
+            .filter_map(|(node, (distance, _))| backward.get(node)
+                .map(|(backward_distance, _)| (*node, distance + backward_distance)))
+            .min_by(|(node1, distance1), (node2, distance2)|
+                distance1.total_cmp(distance2).then(node1.cmp(node2)))?;
+
+        let mut arcs = Vec::new();
+        let mut current = meeting;
+        while let Some(arc) = forward[&current].1 {
+            arcs.push(arc);
+            current = self.arcs[arc].source;
+        }
+        arcs.reverse();
+
+        current = meeting;
+        while let Some(arc) = backward[&current].1 {
+            arcs.push(arc);
+            current = self.arcs[arc].destination;
+        }
+
+        return Some((distance, arcs));
+    }
+
+    fn upward_search(&self,
+                     start: usize,
+                     adjacency: &[Vec<usize>],
+                     next: fn(&Arc) -> usize,
+                     expansions: &mut Expansions) -> HashMap<usize, (f32, Option<usize>)> {
+        let mut settled: HashMap<usize, (f32, Option<usize>)> = HashMap::new();
+        let mut parents: HashMap<usize, Option<usize>> = HashMap::from([(start, None)]);
+        let mut queue: DoublePriorityQueue<usize, NotNan<f32>> = DoublePriorityQueue::new();
+        queue.push(start, NotNan::new(0.0).unwrap());
+
+        while let Some((node, distance)) = queue.pop_min() {
+            settled.insert(node, (distance.into_inner(), parents[&node]));
+            expansions.record(node);
+
+            for arc in &adjacency[node] {
+                let neighbour = next(&self.arcs[*arc]);
+                if settled.contains_key(&neighbour) {
+                    continue;
+                }
+
+                let cost = distance + self.arcs[*arc].weight;
+                if queue.get_priority(&neighbour).is_none_or(|queued| *queued > cost) {
+                    queue.push(neighbour, cost);
+                    parents.insert(neighbour, Some(*arc));
+                }
+            }
+        }
+
+        return settled;
+    }
+}
+
+
+#[cfg(test)]
+fn road_graph() -> Graph {
+    let matrix: &[&[f32]] = &[
+        &[0.0, 4.0, 0.0, 0.0, 0.0, 0.0, 0.0, 8.0, 0.0],
+        &[4.0, 0.0, 8.0, 0.0, 0.0, 0.0, 0.0, 11.0, 0.0],
+        &[0.0, 8.0, 0.0, 7.0, 0.0, 4.0, 0.0, 0.0, 2.0],
+        &[0.0, 0.0, 7.0, 0.0, 9.0, 14.0, 0.0, 0.0, 0.0],
+        &[0.0, 0.0, 0.0, 9.0, 0.0, 10.0, 0.0, 0.0, 0.0],
+        &[0.0, 0.0, 4.0, 14.0, 10.0, 0.0, 2.0, 0.0, 0.0],
+        &[0.0, 0.0, 0.0, 0.0, 0.0, 2.0, 0.0, 1.0, 6.0],
+        &[8.0, 11.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 7.0],
+        &[0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 6.0, 7.0, 0.0]
+    ];
+
+    return Graph::from_adjacency_matrix(matrix);
+}
+
+#[test]
+fn contraction_hierarchy_should_match_dijkstra_distances() {
+    let graph = road_graph();
+    let hierarchy = ContractionHierarchy::from(&graph);
+
+    for source in 0..9 {
+        for target in 0..9 {
+            let expected: f32 = in_graph(source, target, &graph, Box::from(Dijkstra {})).edges.iter()
+                .map(|edge| edge.weight)
+                .sum();
+
+            assert_eq!(Some(expected), hierarchy.distance(source, target));
+        }
+    }
+}
+
+#[test]
+fn contraction_hierarchy_should_unpack_shortcuts_into_connected_edges() {
+    let graph = road_graph();
+    let hierarchy = ContractionHierarchy::from(&graph);
+
+    let path = hierarchy.path(0, 4, &graph);
+    let cost: f32 = path.edges.iter().map(|edge| edge.weight).sum();
+
+    assert_eq!(Some(21.0), hierarchy.distance(0, 4));
+    assert_eq!(21.0, cost);
+    assert_eq!(0, path.edges[0].source);
+    assert_eq!(4, path.edges.last().unwrap().destination);
+    for pair in path.edges.windows(2) {
+        assert_eq!(pair[0].destination, pair[1].source);
+    }
+}
+
+#[test]
+fn contraction_hierarchy_should_trace_settled_nodes() {
+    let graph = road_graph();
+    let hierarchy = ContractionHierarchy::from(&graph);
+
+    let traced = hierarchy.path_traced(0, 4, &graph);
+    let expanded = traced.expanded.unwrap();
+
+    let indices = |path: &Graph| path.edges.iter().map(|edge| edge.index()).collect::<Vec<usize>>();
+    assert_eq!(indices(&hierarchy.path(0, 4, &graph)), indices(&traced.path));
+    assert_eq!(Some(&0), expanded.first());
+    assert!(expanded.contains(&4));
+    assert!(expanded.len() < 2 * graph.node_count);
+}
+
+#[test]
+fn contraction_hierarchy_should_add_shortcuts_for_line() {
+    let graph = Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 1.0),
+        Edge::from(1, 1, 2, 1.0),
+        Edge::from(2, 2, 3, 1.0),
+    ]));
+    let hierarchy = ContractionHierarchy::from(&graph);
+
+    assert_eq!(Some(vec![0, 1, 2]), hierarchy.edge_indices(0, 3));
+    assert_eq!(None, hierarchy.distance(3, 0));
+    assert!(hierarchy.rank(0).is_some());
+}
+
+#[test]
+fn contraction_hierarchy_with_unknown_node_should_return_none() {
+    let hierarchy = ContractionHierarchy::from(&road_graph());
+
+    assert_eq!(None, hierarchy.edge_indices(0, 42));
+    assert!(hierarchy.path(0, 42, &road_graph()).edges.is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn contraction_hierarchy_should_survive_serialization() {
+    let hierarchy = ContractionHierarchy::from(&road_graph());
+
+    let json = serde_json::to_string(&hierarchy).unwrap();
+    let restored: ContractionHierarchy = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(hierarchy, restored);
+    assert_eq!(hierarchy.distance(0, 4), restored.distance(0, 4));
+}
@@ -0,0 +1,283 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::graph::{Edge, Graph};
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Cycle {
+    pub nodes: Vec<usize>,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DagPaths {
+    pub distances: HashMap<usize, f32>,
+    predecessors: HashMap<usize, Edge>,
+}
+
+impl DagPaths {
+    pub fn distance(&self, node_id: usize) -> Option<f32> {
+        return self.distances.get(&node_id).cloned();
+    }
+
+    pub fn path_to(&self, target: usize) -> Graph {
+        let mut edges = Vec::new();
+        let mut current = target;
+
+        while let Some(edge) = self.predecessors.get(&current) {
+            current = edge.source;
+            edges.push(edge.clone());
+        }
+
+        edges.reverse();
+        return Graph::from(edges);
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CriticalPath {
+    pub duration: f32,
+    pub earliest: HashMap<usize, f32>,
+    pub latest: HashMap<usize, f32>,
+    pub path: Graph,
+}
+
+impl CriticalPath {
+    pub fn slack(&self, node_id: usize) -> f32 {
+        return self.latest[&node_id] - self.earliest[&node_id];
+    }
+
+    pub fn is_critical(&self, node_id: usize) -> bool {
+        return self.slack(node_id).abs() <= f32::EPSILON;
+    }
+}
+
+pub fn topological_sort(graph: &Graph) -> Result<Vec<usize>, Cycle> {
+    let mut in_degrees: HashMap<usize, usize> = graph.nodes_lookup.keys().map(|id| (*id, 0)).collect();
+    for edge in &graph.edges {
+        *in_degrees.get_mut(&edge.destination).unwrap() += 1;
+    }
+
+    let mut ready: BinaryHeap<Reverse<usize>> = in_degrees.iter()
+        .filter(|(_, degree)| **degree == 0)
+        .map(|(id, _)| Reverse(*id))
+        .collect();
+    let mut order = Vec::with_capacity(in_degrees.len());
+
+    while let Some(Reverse(node_id)) = ready.pop() {
+        order.push(node_id);
+
+        for edge in &graph.nodes_lookup[&node_id].edges {
+            let degree = in_degrees.get_mut(&edge.destination).unwrap();
+            *degree -= 1;
+
+            if *degree == 0 {
+                ready.push(Reverse(edge.destination));
+            }
+        }
+    }
+
+    if order.len() < in_degrees.len() {
+        return Err(find_cycle(graph, &in_degrees));
+    }
+
+    return Ok(order);
+}
+
+// Every node left with a positive in-degree has a predecessor that is left as well,
+// so walking predecessors backwards must eventually revisit a node.
+fn find_cycle(graph: &Graph, in_degrees: &HashMap<usize, usize>) -> Cycle {
+    let mut predecessors: HashMap<usize, usize> = HashMap::new();
+    for edge in &graph.edges {
+        if in_degrees[&edge.source] > 0 && in_degrees[&edge.destination] > 0 {
+            predecessors.entry(edge.destination).or_insert(edge.source);
+        }
+    }
+
+    let mut current = *in_degrees.iter()
+        .filter(|(_, degree)| **degree > 0)
+        .map(|(id, _)| id)
+        .min()
+        .unwrap();
+    let mut walked: Vec<usize> = Vec::new();
+    let mut seen: HashSet<usize> = HashSet::new();
+
+    while seen.insert(current) {
+        walked.push(current);
+        current = predecessors[&current];
+    }
+
+    let start = walked.iter().position(|id| *id == current).unwrap();
+    let mut nodes = walked.split_off(start);
+    nodes.reverse();
+    nodes.rotate_right(1);
+
+    return Cycle { nodes };
+}
+
+pub fn dag_shortest_path(graph: &Graph, source: usize) -> Result<DagPaths, Cycle> {
+    return dag_paths(graph, &[source], false);
+}
+
+pub fn dag_longest_path(graph: &Graph, source: usize) -> Result<DagPaths, Cycle> {
+    return dag_paths(graph, &[source], true);
+}
+
+pub fn critical_path(graph: &Graph) -> Result<CriticalPath, Cycle> {
+    let order = topological_sort(graph)?;
+    let destinations: HashSet<usize> = graph.edges.iter().map(|edge| edge.destination).collect();
+    let sources: Vec<usize> = order.iter()
+        .filter(|id| !destinations.contains(id))
+        .cloned()
+        .collect();
+
+    let longest = dag_paths(graph, &sources, true)?;
+    let (end, duration) = longest.distances.iter()
+        .map(|(id, distance)| (*id, *distance))
+        .max_by(|(id1, distance1), (id2, distance2)|
+            distance1.total_cmp(distance2).then(id2.cmp(id1)))
+        .unwrap_or((0, 0.0));
+
+    let mut latest: HashMap<usize, f32> = HashMap::new();
+    for node_id in order.iter().rev() {
+        let node_latest = graph.nodes_lookup[node_id].edges.iter()
+            .map(|edge| latest[&edge.destination] - edge.weight)
+            .min_by(|latest1, latest2| latest1.total_cmp(latest2))
+            .unwrap_or(duration);
+        latest.insert(*node_id, node_latest);
+    }
+
+    return Ok(CriticalPath {
+        duration,
+        path: longest.path_to(end),
+        earliest: longest.distances,
+        latest,
+    });
+}
+
+fn dag_paths(graph: &Graph, sources: &[usize], longest: bool) -> Result<DagPaths, Cycle> {
+    let order = topological_sort(graph)?;
+    let mut distances: HashMap<usize, f32> = sources.iter()
+        .filter(|id| graph.nodes_lookup.contains_key(id))
+        .map(|id| (*id, 0.0))
+        .collect();
+    let mut predecessors: HashMap<usize, Edge> = HashMap::new();
+
+    for node_id in order {
+        let distance = match distances.get(&node_id) {
+            None => continue,
+            Some(distance) => *distance
+        };
+
+        for edge in &graph.nodes_lookup[&node_id].edges {
+            let candidate = distance + edge.weight;
+            let better = match distances.get(&edge.destination) {
+                None => true,
+                Some(current) if longest => candidate > *current,
+                Some(current) => candidate < *current,
+            };
+
+            if better {
+                distances.insert(edge.destination, candidate);
+                predecessors.insert(edge.destination, edge.clone());
+            }
+        }
+    }
+
+    return Ok(DagPaths {
+        distances,
+        predecessors,
+    });
+}
+
+
+#[cfg(test)]
+fn tech_tree() -> Graph {
+    return Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 3.0),
+        Edge::from(1, 0, 2, 2.0),
+        Edge::from(2, 1, 3, 4.0),
+        Edge::from(3, 2, 3, 1.0),
+        Edge::from(4, 2, 4, 6.0),
+        Edge::from(5, 3, 5, 1.0),
+        Edge::from(6, 4, 5, 1.0),
+    ]));
+}
+
+#[test]
+fn topological_sort_should_order_nodes() {
+    let graph = tech_tree();
+    let order = topological_sort(&graph).unwrap();
+
+    assert_eq!(vec![0, 1, 2, 3, 4, 5], order);
+    for edge in &graph.edges {
+        let source = order.iter().position(|id| *id == edge.source).unwrap();
+        let destination = order.iter().position(|id| *id == edge.destination).unwrap();
+        assert!(source < destination);
+    }
+}
+
+#[test]
+fn topological_sort_should_report_cycle() {
+    let graph = Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 1.0),
+        Edge::from(1, 1, 2, 1.0),
+        Edge::from(2, 2, 3, 1.0),
+        Edge::from(3, 3, 1, 1.0),
+        Edge::from(4, 3, 4, 1.0),
+    ]));
+
+    let cycle = topological_sort(&graph).unwrap_err();
+
+    assert_eq!(vec![1, 2, 3], cycle.nodes);
+}
+
+#[test]
+fn dag_shortest_path_should_return_distances_and_path() {
+    let paths = dag_shortest_path(&tech_tree(), 0).unwrap();
+
+    assert_eq!(Some(3.0), paths.distance(3));
+    assert_eq!(Some(4.0), paths.distance(5));
+
+    let path = paths.path_to(5);
+    assert_eq!(3, path.edges.len());
+    assert_eq!(0, path.edges[0].source);
+    assert_eq!(5, path.edges[2].destination);
+}
+
+#[test]
+fn dag_longest_path_should_return_distances_and_path() {
+    let paths = dag_longest_path(&tech_tree(), 0).unwrap();
+
+    assert_eq!(Some(7.0), paths.distance(3));
+    assert_eq!(Some(9.0), paths.distance(5));
+
+    let path = paths.path_to(5);
+    assert_eq!(3, path.edges.len());
+    assert_eq!(4, path.edges[1].destination);
+}
+
+#[test]
+fn dag_path_to_unreachable_node_should_be_empty() {
+    let paths = dag_shortest_path(&tech_tree(), 2).unwrap();
+
+    assert_eq!(None, paths.distance(1));
+    assert!(paths.path_to(1).edges.is_empty());
+}
+
+#[test]
+fn critical_path_should_return_duration_and_slack() {
+    let critical = critical_path(&tech_tree()).unwrap();
+
+    assert_eq!(9.0, critical.duration);
+    assert_eq!(3, critical.path.edges.len());
+    assert!(critical.is_critical(0));
+    assert!(critical.is_critical(2));
+    assert!(critical.is_critical(4));
+    assert!(critical.is_critical(5));
+    assert_eq!(1.0, critical.slack(1));
+    assert_eq!(1.0, critical.slack(3));
+}
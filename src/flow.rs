@@ -0,0 +1,471 @@
+use std::collections::{HashSet, VecDeque};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::graph::{Edge, Graph};
+use crate::spanning::NodeIndex;
+
+const EPSILON: f32 = 1e-6;
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Flow {
+    pub value: f32,
+    pub flows: Vec<(Edge, f32)>,
+}
+
+impl Flow {
+    pub fn flow(&self, edge: &Edge) -> f32 {
+        return self.flows.iter()
+            .find(|(flow_edge, _)| flow_edge.index() == edge.index())
+            .map_or(0.0, |(_, flow)| *flow);
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CostFlow {
+    pub flow: Flow,
+    pub cost: f32,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MinCut {
+    pub capacity: f32,
+    pub source_side: HashSet<usize>,
+    pub edges: Vec<Edge>,
+}
+
+struct Arc {
+    to: usize,
+    capacity: f32,
+    cost: f32,
+}
+
+// Residual network over dense node indices. The forward arc of the edge at position p in graph.edges is 2p,
+// its reverse arc is 2p + 1, hence the flow of an edge is the capacity of its reverse arc.
+struct Residual {
+    arcs: Vec<Arc>,
+    adjacency: Vec<Vec<usize>>,
+    node_index: NodeIndex,
+}
+
+impl Residual {
+    fn from(graph: &Graph, cost: &dyn Fn(&Edge) -> f32) -> Residual {
+        let node_index = NodeIndex::from(graph);
+        let mut arcs = Vec::with_capacity(graph.edges.len() * 2);
+        let mut adjacency = vec![Vec::new(); node_index.ids.len()];
+
+        for edge in &graph.edges {
+            let (source, destination) = (node_index.index(edge.source), node_index.index(edge.destination));
+            let edge_cost = cost(edge);
+
+            adjacency[source].push(arcs.len());
+            arcs.push(Arc { to: destination, capacity: edge.weight.max(0.0), cost: edge_cost });
+            adjacency[destination].push(arcs.len());
+            arcs.push(Arc { to: source, capacity: 0.0, cost: -edge_cost });
+        }
+
+        return Residual {
+            arcs,
+            adjacency,
+            node_index,
+        };
+    }
+
+    fn terminals(&self, source: usize, sink: usize) -> Option<(usize, usize)> {
+        let source = self.node_index.lookup.get(&source)?;
+        let sink = self.node_index.lookup.get(&sink)?;
+
+        if source == sink {
+            return None;
+        }
+
+        return Some((*source, *sink));
+    }
+
+    fn push(&mut self, arc: usize, amount: f32) {
+        self.arcs[arc].capacity -= amount;
+        self.arcs[arc ^ 1].capacity += amount;
+    }
+
+    fn to_flow(&self, graph: &Graph, value: f32) -> Flow {
+        let flows = graph.edges.iter().enumerate()
+            .map(|(position, edge)| (edge.clone(), self.arcs[position * 2 + 1].capacity))
+            .filter(|(_, flow)| *flow > EPSILON)
+            .collect();
+
+        return Flow { value, flows };
+    }
+
+    fn levels(&self, source: usize) -> Vec<Option<usize>> {
+        let mut levels = vec![None; self.adjacency.len()];
+        let mut queue = VecDeque::from([source]);
+        levels[source] = Some(0);
+
+        while let Some(node) = queue.pop_front() {
+            for arc in &self.adjacency[node] {
+                let to = self.arcs[*arc].to;
+
+                if levels[to].is_none() && self.arcs[*arc].capacity > EPSILON {
+                    levels[to] = levels[node].map(|level| level + 1);
+                    queue.push_back(to);
+                }
+            }
+        }
+
+        return levels;
+    }
+}
+
+fn empty_flow() -> Flow {
+    return Flow { value: 0.0, flows: Vec::new() };
+}
+
+pub fn edmonds_karp(graph: &Graph, source: usize, sink: usize) -> Flow {
+    let mut residual = Residual::from(graph, &|_| 0.0);
+    let (source_index, sink_index) = match residual.terminals(source, sink) {
+        None => return empty_flow(),
+        Some(terminals) => terminals
+    };
+
+    let mut value = 0.0;
+
+    loop {
+        let mut parent_arcs: Vec<Option<usize>> = vec![None; residual.adjacency.len()];
+        let mut queue = VecDeque::from([source_index]);
+
+        while let Some(node) = queue.pop_front() {
+            for arc in &residual.adjacency[node] {
+                let to = residual.arcs[*arc].to;
+
+                if to != source_index && parent_arcs[to].is_none() && residual.arcs[*arc].capacity > EPSILON {
+                    parent_arcs[to] = Some(*arc);
+                    queue.push_back(to);
+                }
+            }
+        }
+
+        if parent_arcs[sink_index].is_none() {
+            break;
+        }
+
+        let path = walk_back(&parent_arcs, &residual, sink_index);
+        let bottleneck = path.iter()
+            .map(|arc| residual.arcs[*arc].capacity)
+            .fold(f32::INFINITY, f32::min);
+
+        for arc in path {
+            residual.push(arc, bottleneck);
+        }
+        value += bottleneck;
+    }
+
+    return residual.to_flow(graph, value);
+}
+
+pub fn dinic(graph: &Graph, source: usize, sink: usize) -> Flow {
+    let mut residual = Residual::from(graph, &|_| 0.0);
+    let (source_index, sink_index) = match residual.terminals(source, sink) {
+        None => return empty_flow(),
+        Some(terminals) => terminals
+    };
+
+    let value = blocking_flows(&mut residual, source_index, sink_index);
+    return residual.to_flow(graph, value);
+}
+
+fn blocking_flows(residual: &mut Residual, source: usize, sink: usize) -> f32 {
+    let mut value = 0.0;
+
+    loop {
+        let levels = residual.levels(source);
+        if levels[sink].is_none() {
+            break;
+        }
+
+        let mut next_arcs = vec![0; residual.adjacency.len()];
+        loop {
+            let pushed = augment(residual, &levels, &mut next_arcs, source, sink);
+            if pushed <= EPSILON {
+                break;
+            }
+            value += pushed;
+        }
+    }
+
+    return value;
+}
+
+// Depth first search along the level graph with an explicit stack of arcs, so long paths don't overflow the call stack.
+// The next arc of a node only moves on, once the node is a dead end behind it.
+fn augment(residual: &mut Residual,
+           levels: &[Option<usize>],
+           next_arcs: &mut [usize],
+           source: usize,
+           sink: usize) -> f32 {
+    let mut path: Vec<usize> = Vec::new();
+    let mut node = source;
+
+    loop {
+        if node == sink {
+            let pushed = path.iter().map(|arc| residual.arcs[*arc].capacity).fold(f32::INFINITY, f32::min);
+            for arc in path {
+                residual.push(arc, pushed);
+            }
+            return pushed;
+        }
+
+        if next_arcs[node] < residual.adjacency[node].len() {
+            let arc = residual.adjacency[node][next_arcs[node]];
+            let (to, capacity) = (residual.arcs[arc].to, residual.arcs[arc].capacity);
+
+            if capacity > EPSILON && levels[to] == levels[node].map(|level| level + 1) {
+                path.push(arc);
+                node = to;
+            } else {
+                next_arcs[node] += 1;
+            }
+            continue;
+        }
+
+        // dead end, retreat and skip the arc leading here
+        if path.pop().is_none() {
+            return 0.0;
+        }
+        node = path.last().map_or(source, |arc| residual.arcs[*arc].to);
+        next_arcs[node] += 1;
+    }
+}
+
+pub fn min_cut(graph: &Graph, source: usize, sink: usize) -> MinCut {
+    let mut residual = Residual::from(graph, &|_| 0.0);
+    let (source_index, sink_index) = match residual.terminals(source, sink) {
+        None => return MinCut { capacity: 0.0, source_side: HashSet::new(), edges: Vec::new() },
+        Some(terminals) => terminals
+    };
+
+    let capacity = blocking_flows(&mut residual, source_index, sink_index);
+    let levels = residual.levels(source_index);
+    let source_side: HashSet<usize> = residual.node_index.ids.iter().enumerate()
+        .filter(|(index, _)| levels[*index].is_some())
+        .map(|(_, node_id)| *node_id)
+        .collect();
+
+    let edges = graph.edges.iter()
+        .filter(|edge| source_side.contains(&edge.source) && !source_side.contains(&edge.destination))
+        .cloned()
+        .collect();
+
+    return MinCut {
+        capacity,
+        source_side,
+        edges,
+    };
+}
+
+// Costs may be negative, as long as they form no negative cycle reachable from the source. Such a cycle would make
+// the flow arbitrarily cheap, hence None is returned.
+pub fn min_cost_max_flow(graph: &Graph, source: usize, sink: usize, cost: &dyn Fn(&Edge) -> f32) -> Option<CostFlow> {
+    let mut residual = Residual::from(graph, cost);
+    let (source_index, sink_index) = match residual.terminals(source, sink) {
+        None => return Some(CostFlow { flow: empty_flow(), cost: 0.0 }),
+        Some(terminals) => terminals
+    };
+
+    let node_count = residual.adjacency.len();
+    let (mut value, mut total_cost) = (0.0, 0.0);
+
+    loop {
+        // Bellman-Ford with a queue, since reverse arcs have negative costs
+        let mut distances = vec![f32::INFINITY; node_count];
+        let mut parent_arcs: Vec<Option<usize>> = vec![None; node_count];
+        let mut queued = vec![false; node_count];
+        let mut relaxations = vec![0; node_count];
+        let mut queue = VecDeque::from([source_index]);
+        distances[source_index] = 0.0;
+
+        while let Some(node) = queue.pop_front() {
+            queued[node] = false;
+
+            for arc in &residual.adjacency[node] {
+                let Arc { to, capacity, cost } = residual.arcs[*arc];
+
+                if capacity > EPSILON && distances[node] + cost < distances[to] - EPSILON {
+                    distances[to] = distances[node] + cost;
+                    parent_arcs[to] = Some(*arc);
+
+                    // without negative cycles, a shortest path has less than node_count arcs
+                    relaxations[to] += 1;
+                    if relaxations[to] >= node_count {
+                        return None;
+                    }
+
+                    if !queued[to] {
+                        queued[to] = true;
+                        queue.push_back(to);
+                    }
+                }
+            }
+        }
+
+        if parent_arcs[sink_index].is_none() {
+            break;
+        }
+
+        let path = walk_back(&parent_arcs, &residual, sink_index);
+        let bottleneck = path.iter()
+            .map(|arc| residual.arcs[*arc].capacity)
+            .fold(f32::INFINITY, f32::min);
+
+        for arc in path {
+            residual.push(arc, bottleneck);
+        }
+        value += bottleneck;
+        total_cost += bottleneck * distances[sink_index];
+    }
+
+    return Some(CostFlow {
+        flow: residual.to_flow(graph, value),
+        cost: total_cost,
+    });
+}
+
+fn walk_back(parent_arcs: &[Option<usize>], residual: &Residual, sink: usize) -> Vec<usize> {
+    let mut path = Vec::new();
+    let mut current = sink;
+
+    while let Some(arc) = parent_arcs[current] {
+        path.push(arc);
+        current = residual.arcs[arc ^ 1].to;
+    }
+
+    return path;
+}
+
+
+#[cfg(test)]
+fn network() -> Graph {
+    return Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 16.0),
+        Edge::from(1, 0, 2, 13.0),
+        Edge::from(2, 1, 2, 10.0),
+        Edge::from(3, 2, 1, 4.0),
+        Edge::from(4, 1, 3, 12.0),
+        Edge::from(5, 3, 2, 9.0),
+        Edge::from(6, 2, 4, 14.0),
+        Edge::from(7, 4, 3, 7.0),
+        Edge::from(8, 3, 5, 20.0),
+        Edge::from(9, 4, 5, 4.0),
+    ]));
+}
+
+#[test]
+fn edmonds_karp_should_return_max_flow() {
+    let graph = network();
+    let flow = edmonds_karp(&graph, 0, 5);
+
+    assert_eq!(23.0, flow.value);
+    assert_eq!(23.0, flow.flow(&graph.edges[8]) + flow.flow(&graph.edges[9]));
+}
+
+#[test]
+fn dinic_should_return_max_flow() {
+    let flow = dinic(&network(), 0, 5);
+
+    assert_eq!(23.0, flow.value);
+}
+
+#[test]
+fn max_flow_should_respect_capacity_and_conservation() {
+    let graph = network();
+
+    for flow in [edmonds_karp(&graph, 0, 5), dinic(&graph, 0, 5)] {
+        for (edge, amount) in &flow.flows {
+            assert!(*amount <= edge.weight);
+        }
+
+        for node_id in 1..5 {
+            let incoming: f32 = flow.flows.iter().filter(|(edge, _)| edge.destination == node_id).map(|(_, f)| f).sum();
+            let outgoing: f32 = flow.flows.iter().filter(|(edge, _)| edge.source == node_id).map(|(_, f)| f).sum();
+            assert_eq!(incoming, outgoing);
+        }
+    }
+}
+
+#[test]
+fn max_flow_with_unknown_sink_should_be_empty() {
+    let flow = dinic(&network(), 0, 42);
+
+    assert_eq!(0.0, flow.value);
+    assert!(flow.flows.is_empty());
+}
+
+#[test]
+fn min_cut_should_separate_source_and_sink() {
+    let cut = min_cut(&network(), 0, 5);
+    let cut_capacity: f32 = cut.edges.iter().map(|edge| edge.weight).sum();
+
+    assert_eq!(23.0, cut.capacity);
+    assert_eq!(23.0, cut_capacity);
+    assert!(cut.source_side.contains(&0));
+    assert!(!cut.source_side.contains(&5));
+}
+
+#[test]
+fn min_cost_max_flow_should_prefer_cheap_edges() {
+    // units 0 and 1 are assigned to targets 2 and 3 via source 10 and sink 11
+    let graph = Graph::from(Vec::from([
+        Edge::from(0, 10, 0, 1.0),
+        Edge::from(1, 10, 1, 1.0),
+        Edge::from(2, 0, 2, 1.0),
+        Edge::from(3, 0, 3, 1.0),
+        Edge::from(4, 1, 2, 1.0),
+        Edge::from(5, 1, 3, 1.0),
+        Edge::from(6, 2, 11, 1.0),
+        Edge::from(7, 3, 11, 1.0),
+    ]));
+    let costs = [0.0, 0.0, 1.0, 4.0, 2.0, 6.0, 0.0, 0.0];
+
+    let result = min_cost_max_flow(&graph, 10, 11, &|edge| costs[edge.index()]).unwrap();
+
+    assert_eq!(2.0, result.flow.value);
+    assert_eq!(6.0, result.cost);
+    assert_eq!(1.0, result.flow.flow(&graph.edges[3]));
+    assert_eq!(1.0, result.flow.flow(&graph.edges[4]));
+}
+
+#[test]
+fn min_cost_max_flow_should_allow_negative_costs() {
+    let graph = Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 1.0),
+        Edge::from(1, 1, 2, 1.0),
+        Edge::from(2, 0, 2, 1.0),
+    ]));
+    let costs = [-2.0, 1.0, 0.0];
+
+    let result = min_cost_max_flow(&graph, 0, 2, &|edge| costs[edge.index()]).unwrap();
+
+    assert_eq!(2.0, result.flow.value);
+    assert_eq!(-1.0, result.cost);
+}
+
+#[test]
+fn min_cost_max_flow_should_return_none_on_negative_cycle() {
+    let graph = Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 1.0),
+        Edge::from(1, 1, 2, 1.0),
+        Edge::from(2, 2, 1, 1.0),
+        Edge::from(3, 2, 3, 1.0),
+    ]));
+    let costs = [1.0, -5.0, 1.0, 1.0];
+
+    assert!(min_cost_max_flow(&graph, 0, 3, &|edge| costs[edge.index()]).is_none());
+}
+
+#[test]
+fn dinic_should_handle_long_paths() {
+    let length = 200_000;
+    let graph = Graph::from((0..length).map(|index| Edge::from(index, index, index + 1, 2.0)).collect::<Vec<Edge>>());
+
+    assert_eq!(2.0, dinic(&graph, 0, length).value);
+}
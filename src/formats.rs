@@ -0,0 +1,473 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::graph::{Edge, Graph};
+use crate::node::Vec3;
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FormatError {
+    // Malformed line, counted from 0.
+    Line(usize),
+    // Element or attribute missing in a GraphML document.
+    Missing(String),
+    // No edge index is left after the largest one for the reverse of this undirected GraphML edge.
+    ReverseIndex(String),
+}
+
+// DIMACS shortest path graph ("a u v w" arcs) with optional coordinates ("v id x y"). DIMACS counts node ids from 1,
+// the graph from 0. Edge indices follow the order of the arcs.
+pub fn read_dimacs(graph: &str, coordinates: Option<&str>) -> Result<Graph, FormatError> {
+    let mut edges = Vec::new();
+    for (line_number, fields) in dimacs_lines(graph, "a") {
+        let (source, destination, weight) = match fields[..] {
+            [source, destination, weight] => (dimacs_id(source, line_number)?, dimacs_id(destination, line_number)?,
+                                              weight.parse::<f32>().map_err(|_| FormatError::Line(line_number))?),
+            _ => return Err(FormatError::Line(line_number))
+        };
+        edges.push(Edge::from(edges.len(), source, destination, weight));
+    }
+
+    let mut result = Graph::from(edges);
+    if let Some(coordinates) = coordinates {
+        let mut positions = HashMap::new();
+        for (line_number, fields) in dimacs_lines(coordinates, "v") {
+            let coordinate = |field: &str| field.parse::<f32>().map_err(|_| FormatError::Line(line_number));
+            match fields[..] {
+                [node, x, y] => { positions.insert(dimacs_id(node, line_number)?, Vec3::from(coordinate(x)?, coordinate(y)?, 0.0)); }
+                _ => return Err(FormatError::Line(line_number))
+            }
+        }
+        result.offer_positions(positions);
+    }
+
+    return Ok(result);
+}
+
+pub fn write_dimacs(graph: &Graph) -> String {
+    let mut text = format!("p sp {} {}\n", node_range(graph), graph.edges.len());
+    for edge in &graph.edges {
+        writeln!(text, "a {} {} {}", edge.source + 1, edge.destination + 1, edge.weight).unwrap();
+    }
+
+    return text;
+}
+
+// None, if the graph has no positions. The z coordinate is dropped.
+pub fn write_dimacs_coordinates(graph: &Graph) -> Option<String> {
+    let positions = graph.node_position_lookup.as_ref()?;
+    let mut text = format!("p aux sp co {}\n", positions.len());
+    for node_id in sorted(positions.keys()) {
+        let position = &positions[&node_id];
+        writeln!(text, "v {} {} {}", node_id + 1, position.x, position.y).unwrap();
+    }
+
+    return Some(text);
+}
+
+fn dimacs_lines<'a>(text: &'a str, kind: &'a str) -> impl Iterator<Item=(usize, Vec<&'a str>)> {
+    return text.lines().enumerate()
+        .map(|(line_number, line)| (line_number, line.split_whitespace().collect::<Vec<&str>>()))
+        .filter(move |(_, fields)| fields.first() == Some(&kind))
+        .map(|(line_number, fields)| (line_number, fields[1..].to_vec()));
+}
+
+fn dimacs_id(field: &str, line_number: usize) -> Result<usize, FormatError> {
+    return field.parse::<usize>().ok().and_then(|id| id.checked_sub(1)).ok_or(FormatError::Line(line_number));
+}
+
+// Source, destination and weight per line, the weight defaults to 1. A header line is skipped.
+pub fn read_edge_list(text: &str, delimiter: char) -> Result<Graph, FormatError> {
+    let mut edges = Vec::new();
+
+    for (line_number, line) in text.lines().enumerate() {
+        let fields: Vec<&str> = line.split(delimiter).map(|field| field.trim()).collect();
+        if line.trim().is_empty() || (line_number == 0 && fields[0].parse::<usize>().is_err()) {
+            continue;
+        }
+
+        let id = |field: &str| field.parse::<usize>().map_err(|_| FormatError::Line(line_number));
+        let (source, destination, weight) = match fields[..] {
+            [source, destination] => (id(source)?, id(destination)?, 1.0),
+            [source, destination, weight] => (id(source)?, id(destination)?,
+                                              weight.parse::<f32>().map_err(|_| FormatError::Line(line_number))?),
+            _ => return Err(FormatError::Line(line_number))
+        };
+        edges.push(Edge::from(edges.len(), source, destination, weight));
+    }
+
+    return Ok(Graph::from(edges));
+}
+
+pub fn write_edge_list(graph: &Graph, delimiter: char) -> String {
+    let mut text = format!("source{delimiter}destination{delimiter}weight\n");
+    for edge in &graph.edges {
+        writeln!(text, "{}{delimiter}{}{delimiter}{}", edge.source, edge.destination, edge.weight).unwrap();
+    }
+
+    return text;
+}
+
+// Directed GraphML with a weight per edge and x, y, z per node, if the graph has positions.
+pub fn write_graphml(graph: &Graph) -> String {
+    let mut text = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+        <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n\
+        \x20 <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"double\"/>\n");
+    for axis in ["x", "y", "z"] {
+        writeln!(text, "  <key id=\"{axis}\" for=\"node\" attr.name=\"{axis}\" attr.type=\"double\"/>").unwrap();
+    }
+    text.push_str("  <graph id=\"G\" edgedefault=\"directed\">\n");
+
+    let mut node_ids: Vec<usize> = graph.nodes_lookup.keys().cloned().collect();
+    if let Some(positions) = &graph.node_position_lookup {
+        node_ids.extend(positions.keys().filter(|node_id| !graph.nodes_lookup.contains_key(node_id)));
+    }
+
+    for node_id in sorted(node_ids.iter()) {
+        match graph.node_position_lookup.as_ref().and_then(|positions| positions.get(&node_id)) {
+            None => writeln!(text, "    <node id=\"{node_id}\"/>").unwrap(),
+            Some(position) => writeln!(text, "    <node id=\"{node_id}\"><data key=\"x\">{}</data><data key=\"y\">{}</data>\
+                <data key=\"z\">{}</data></node>", position.x, position.y, position.z).unwrap()
+        }
+    }
+
+    for edge in &graph.edges {
+        writeln!(text, "    <edge id=\"e{}\" source=\"{}\" target=\"{}\"><data key=\"weight\">{}</data></edge>",
+                 edge.index(), edge.source, edge.destination, edge.weight).unwrap();
+    }
+
+    text.push_str("  </graph>\n</graphml>\n");
+    return text;
+}
+
+// Reads nodes, edges and the data keys named weight, x, y and z, keys without a name are matched by their id. Graph data
+// is ignored. Unique numeric node ids and edge ids like "e3" are kept, otherwise nodes and edges are numbered in order. Edges without weight get
+// 1. Undirected edges, by the graph's edgedefault or their own directed attribute, are followed by a reverse edge,
+// numbered after all other edges.
+pub fn read_graphml(text: &str) -> Result<Graph, FormatError> {
+    let mut key_names: HashMap<String, String> = HashMap::new();
+    let mut nodes: Vec<(String, HashMap<String, f32>)> = Vec::new();
+    let mut edges: Vec<GraphMlEdge> = Vec::new();
+    let mut data_key: Option<String> = None;
+    let mut open: Option<&str> = None;
+    let mut undirected = false;
+
+    let text = without_comments(text)?;
+    for piece in text.split('<').skip(1) {
+        let (tag, content) = piece.split_once('>').ok_or(FormatError::Missing(">".to_string()))?;
+        let tag = tag.trim_end();
+        let name = tag.split_whitespace().next().unwrap_or("").trim_end_matches('/');
+        let closed = tag.ends_with('/');
+
+        match name {
+            "key" => {
+                let id = attribute(tag, "id")?;
+                key_names.insert(id.clone(), attribute(tag, "attr.name").unwrap_or(id));
+            }
+            "graph" => undirected = attribute(tag, "edgedefault").is_ok_and(|default| default == "undirected"),
+            "node" => nodes.push((attribute(tag, "id")?, HashMap::new())),
+            "edge" => edges.push(GraphMlEdge {
+                id: attribute(tag, "id").unwrap_or_default(),
+                source: attribute(tag, "source")?,
+                target: attribute(tag, "target")?,
+                undirected: attribute(tag, "directed").map_or(undirected, |directed| directed == "false"),
+                data: HashMap::new(),
+            }),
+            "data" => data_key = Some(attribute(tag, "key")?),
+            "/data" => data_key = None,
+            "/node" | "/edge" => open = None,
+            _ => continue
+        }
+
+        if (name == "node" || name == "edge") && !closed {
+            open = Some(name);
+        }
+
+        // data values belong to the node or edge around them, graph data is ignored
+        if let (Some(key), Ok(value)) = (&data_key, content.trim().parse::<f32>()) {
+            let attribute_name = key_names.get(key).cloned().unwrap_or(key.clone());
+            let data = match open {
+                Some("node") => &mut nodes.last_mut().unwrap().1,
+                Some("edge") => &mut edges.last_mut().unwrap().data,
+                _ => continue
+            };
+            data.insert(attribute_name, value);
+        }
+    }
+
+    let node_ids = numbered(nodes.iter().map(|(id, _)| id.as_str()), "");
+    let lookup: HashMap<&str, usize> = nodes.iter().map(|(id, _)| id.as_str()).zip(node_ids.iter().cloned()).collect();
+    let node_id = |id: &str| lookup.get(id).cloned().ok_or(FormatError::Missing(format!("node {id}")));
+
+    let edge_indices = numbered(edges.iter().map(|edge| edge.id.as_str()), "e");
+    let mut next_index = edge_indices.iter().max().map_or(Some(0), |index| index.checked_add(1));
+    let mut graph_edges = Vec::new();
+    let mut reverse_edges = Vec::new();
+    for (edge, index) in edges.iter().zip(edge_indices) {
+        let (source, target, weight) = (node_id(&edge.source)?, node_id(&edge.target)?, *edge.data.get("weight").unwrap_or(&1.0));
+        graph_edges.push(Edge::from(index, source, target, weight));
+
+        if edge.undirected {
+            let reverse_index = next_index.ok_or(FormatError::ReverseIndex(edge.id.clone()))?;
+            reverse_edges.push(Edge::from(reverse_index, target, source, weight));
+            next_index = reverse_index.checked_add(1);
+        }
+    }
+    graph_edges.extend(reverse_edges);
+
+    let mut graph = Graph::from(graph_edges);
+    let positions: HashMap<usize, Vec3> = nodes.iter().zip(node_ids)
+        .filter(|((_, data), _)| data.contains_key("x") && data.contains_key("y"))
+        .map(|((_, data), node_id)| (node_id, Vec3::from(data["x"], data["y"], *data.get("z").unwrap_or(&0.0))))
+        .collect();
+    if !positions.is_empty() {
+        graph.offer_positions(positions);
+    }
+
+    return Ok(graph);
+}
+
+struct GraphMlEdge {
+    id: String,
+    source: String,
+    target: String,
+    undirected: bool,
+    data: HashMap<String, f32>,
+}
+
+fn without_comments(text: &str) -> Result<String, FormatError> {
+    let mut kept = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("<!--") {
+        kept.push_str(&rest[..start]);
+        let end = rest[start..].find("-->").ok_or(FormatError::Missing("-->".to_string()))?;
+        rest = &rest[start + end + 3..];
+    }
+
+    kept.push_str(rest);
+    return Ok(kept);
+}
+
+// Attributes are separated by any whitespace and quoted with single or double quotes.
+fn attribute(tag: &str, name: &str) -> Result<String, FormatError> {
+    let mut rest = tag.trim_end_matches('/');
+    rest = &rest[rest.find(char::is_whitespace).unwrap_or(rest.len())..];
+
+    loop {
+        rest = rest.trim_start();
+        let equals = match rest.find('=') {
+            None => break,
+            Some(equals) => equals
+        };
+
+        let key = rest[..equals].trim();
+        rest = rest[equals + 1..].trim_start();
+        let quote = match rest.chars().next() {
+            Some(quote) if quote == '"' || quote == '\'' => quote,
+            _ => break
+        };
+
+        let end = match rest[1..].find(quote) {
+            None => break,
+            Some(end) => end + 1
+        };
+        if key == name {
+            return Ok(unescape(&rest[1..end]));
+        }
+        rest = &rest[end + 1..];
+    }
+
+    return Err(FormatError::Missing(name.to_string()));
+}
+
+fn unescape(value: &str) -> String {
+    return value.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&");
+}
+
+// Numbers behind the prefix if every id has a unique one, otherwise the positions. Ids like "1" and "01" would
+// otherwise collapse into one number.
+fn numbered<'a>(ids: impl Iterator<Item=&'a str>, prefix: &str) -> Vec<usize> {
+    let ids: Vec<&str> = ids.collect();
+    let numbers: Option<Vec<usize>> = ids.iter().map(|id| id.strip_prefix(prefix)?.parse::<usize>().ok()).collect();
+    return match numbers {
+        Some(numbers) if numbers.iter().collect::<HashSet<&usize>>().len() == numbers.len() => numbers,
+        _ => (0..ids.len()).collect()
+    };
+}
+
+fn node_range(graph: &Graph) -> usize {
+    return graph.nodes_lookup.keys().max().map_or(0, |node_id| node_id + 1);
+}
+
+fn sorted<'a>(node_ids: impl Iterator<Item=&'a usize>) -> Vec<usize> {
+    let mut sorted: Vec<usize> = node_ids.cloned().collect();
+    sorted.sort();
+    return sorted;
+}
+
+
+#[cfg(test)]
+fn graph() -> Graph {
+    let mut graph = Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 2.0),
+        Edge::from(1, 1, 2, 0.5),
+        Edge::from(4, 2, 0, 3.0),
+    ]));
+    graph.offer_positions(HashMap::from([
+        (0, Vec3::from(0.0, 0.0, 0.0)),
+        (1, Vec3::from(1.5, 0.0, 0.0)),
+        (2, Vec3::from(1.0, -2.0, 0.0)),
+    ]));
+
+    return graph;
+}
+
+#[cfg(test)]
+fn same_edges(graph1: &Graph, graph2: &Graph) -> bool {
+    return graph1.edges.len() == graph2.edges.len()
+        && graph1.edges.iter().zip(&graph2.edges).all(|(edge1, edge2)| edge1.source == edge2.source
+        && edge1.destination == edge2.destination && edge1.weight == edge2.weight);
+}
+
+#[test]
+fn dimacs_should_read_arcs_and_coordinates() {
+    let graph = read_dimacs("c road network\np sp 3 2\na 1 2 7\na 2 3 4\n",
+                            Some("p aux sp co 3\nv 1 10 20\nv 2 11 21\nv 3 12 22\n")).unwrap();
+
+    assert_eq!(2, graph.edges.len());
+    assert_eq!((0, 1, 7.0), (graph.edges[0].source, graph.edges[0].destination, graph.edges[0].weight));
+    assert_eq!(&Vec3::from(12.0, 22.0, 0.0), graph.get_position(&2));
+    assert_eq!(Some(FormatError::Line(1)), read_dimacs("p sp 1 1\na 0 1 7\n", None).err());
+}
+
+#[test]
+fn dimacs_should_round_trip() {
+    let graph = graph();
+
+    let read = read_dimacs(&write_dimacs(&graph), write_dimacs_coordinates(&graph).as_deref()).unwrap();
+
+    assert!(write_dimacs(&graph).starts_with("p sp 3 3\na 1 2 2\n"));
+    assert!(same_edges(&graph, &read));
+    assert_eq!(&Vec3::from(1.0, -2.0, 0.0), read.get_position(&2));
+}
+
+#[test]
+fn edge_list_should_round_trip() {
+    let graph = graph();
+
+    let csv = read_edge_list(&write_edge_list(&graph, ','), ',').unwrap();
+    let tsv = read_edge_list(&write_edge_list(&graph, '\t'), '\t').unwrap();
+
+    assert!(same_edges(&graph, &csv));
+    assert!(same_edges(&graph, &tsv));
+}
+
+#[test]
+fn edge_list_should_default_weight() {
+    let graph = read_edge_list("3,4\n4,5,2.5\n", ',').unwrap();
+
+    assert_eq!(vec![1.0, 2.5], graph.edges.iter().map(|edge| edge.weight).collect::<Vec<f32>>());
+    assert_eq!(Some(FormatError::Line(1)), read_edge_list("from,to\n3,x\n", ',').err());
+}
+
+#[test]
+fn graphml_should_round_trip() {
+    let graph = graph();
+
+    let read = read_graphml(&write_graphml(&graph)).unwrap();
+
+    assert!(same_edges(&graph, &read));
+    assert_eq!(4, read.edges[2].index());
+    assert_eq!(&Vec3::from(1.5, 0.0, 0.0), read.get_position(&1));
+}
+
+#[test]
+fn graphml_should_number_named_nodes() {
+    let document = "<graphml><key id=\"d0\" for=\"edge\" attr.name=\"weight\" attr.type=\"double\"/>\
+        <graph edgedefault=\"directed\"><node id=\"home\"/><node id=\"work\"/>\
+        <edge source=\"home\" target=\"work\"><data key=\"d0\">12.5</data></edge>\
+        <edge source=\"work\" target=\"home\"/></graph></graphml>";
+
+    let graph = read_graphml(document).unwrap();
+
+    assert_eq!(2, graph.edges.len());
+    assert_eq!((0, 1, 12.5), (graph.edges[0].source, graph.edges[0].destination, graph.edges[0].weight));
+    assert_eq!(1.0, graph.edges[1].weight);
+    assert!(graph.node_position_lookup.is_none());
+    assert_eq!(Some(FormatError::Missing("node pub".to_string())),
+               read_graphml("<graph><node id=\"a\"/><edge source=\"a\" target=\"pub\"/></graph>").err());
+}
+
+#[test]
+fn graphml_should_read_undirected_edges() {
+    let document = "<graphml><graph edgedefault=\"undirected\"><node id=\"0\"/><node id=\"1\"/><node id=\"2\"/>\
+        <edge id=\"e0\" source=\"0\" target=\"1\"/><edge id=\"e1\" source=\"1\" target=\"2\" directed=\"true\"/>\
+        </graph></graphml>";
+    let mixed = "<graphml><graph edgedefault=\"directed\"><node id=\"0\"/><node id=\"1\"/>\
+        <edge source=\"0\" target=\"1\" directed=\"false\"/></graph></graphml>";
+
+    let graph = read_graphml(document).unwrap();
+    let mixed = read_graphml(mixed).unwrap();
+
+    assert_eq!(3, graph.edges.len());
+    assert_eq!((2, 1, 0), (graph.edges[2].index(), graph.edges[2].source, graph.edges[2].destination));
+    assert!(graph.edges.iter().all(|edge| !(edge.source == 2 && edge.destination == 1)));
+    assert_eq!(2, mixed.edges.len());
+    assert_eq!((1, 0), (mixed.edges[1].source, mixed.edges[1].destination));
+}
+
+#[test]
+fn graphml_should_read_any_attribute_layout() {
+    let document = "<?xml version='1.0'?>\n<!-- exported <by> hand -->\n<graphml>\
+        <key id='w' for='edge' attr.name='weight'/><key id=\"x\" for=\"node\"/><key id=\"y\" for=\"node\"/>\
+        <graph><node\tid='A&amp;B'><data key='x'>1</data><data key='y'>2</data></node>\
+        <node\n  id = \"C&lt;D\" /><!-- <edge source='C&lt;D' target='A&amp;B'/> -->\
+        <edge\r\n source='A&amp;B'\ttarget=\"C&lt;D\"><data key=\"w\">2.5</data></edge></graph></graphml>";
+
+    let graph = read_graphml(document).unwrap();
+
+    assert_eq!(1, graph.edges.len());
+    assert_eq!((0, 1, 2.5), (graph.edges[0].source, graph.edges[0].destination, graph.edges[0].weight));
+    assert_eq!(&Vec3::from(1.0, 2.0, 0.0), graph.get_position(&0));
+}
+
+#[test]
+fn graphml_should_reject_unclosed_comments() {
+    assert_eq!(Some(FormatError::Missing("-->".to_string())), read_graphml("<graphml><!-- <node id=\"0\"/>").err());
+}
+
+#[test]
+fn graphml_should_ignore_graph_data() {
+    let document = "<graphml><key id=\"v\" for=\"graph\" attr.name=\"version\"/>\
+        <graph><data key=\"v\">3</data><node id=\"0\"/><node id=\"1\"/><edge source=\"0\" target=\"1\"/></graph></graphml>";
+
+    assert_eq!(1, read_graphml(document).unwrap().edges.len());
+}
+
+#[test]
+fn graphml_should_number_ambiguous_ids_in_order() {
+    let document = "<graph><node id=\"1\"/><node id=\"01\"/><edge id=\"e1\" source=\"1\" target=\"01\"/>\
+        <edge id=\"e01\" source=\"01\" target=\"1\"/></graph>";
+
+    let graph = read_graphml(document).unwrap();
+
+    assert_eq!(vec![(0, 0, 1), (1, 1, 0)], graph.edges.iter()
+        .map(|edge| (edge.index(), edge.source, edge.destination))
+        .collect::<Vec<(usize, usize, usize)>>());
+}
+
+#[test]
+fn graphml_should_reject_undirected_edges_without_reverse_index() {
+    let document = "<graph edgedefault=\"undirected\"><node id=\"0\"/><node id=\"1\"/>\
+        <edge id=\"e18446744073709551615\" source=\"0\" target=\"1\"/></graph>";
+
+    assert_eq!(Some(FormatError::ReverseIndex("e18446744073709551615".to_string())), read_graphml(document).err());
+}
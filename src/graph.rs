@@ -2,12 +2,17 @@ use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 
 use derivative::Derivative;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+use crate::grid::{CostModel, Direction, Grid};
 use crate::node::{Node, Vec3};
-use crate::union_find::UnionFind;
+use crate::spanning;
+use crate::spanning::Spanning;
 
 #[derive(Derivative)]
 #[derivative(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Edge {
     index: usize,
     pub source: usize,
@@ -26,6 +31,10 @@ impl Edge {
             weight,
         };
     }
+
+    pub fn index(&self) -> usize {
+        return self.index;
+    }
 }
 
 pub struct Graph {
@@ -77,6 +86,54 @@ impl Graph {
         return Graph::from(vec);
     }
 
+    // Node ids are the cell node ids, positions are (row, col, 0). Every walkable cell is a node, even without a walkable
+    // neighbour. Blocked cells are left out and edge indices are numbered in order of cells and directions.
+    pub fn from_grid(grid: &Grid, directions: &[Direction], cost_model: CostModel) -> Graph {
+        return Graph::grid_graph(grid, directions, cost_model, false);
+    }
+
+    // Like from_grid, but blocked cells are nodes as well, for searches which may enter them at their cost or see them
+    // unblocked later.
+    pub(crate) fn from_grid_with_blocked(grid: &Grid, directions: &[Direction], cost_model: CostModel) -> Graph {
+        return Graph::grid_graph(grid, directions, cost_model, true);
+    }
+
+    fn grid_graph(grid: &Grid, directions: &[Direction], cost_model: CostModel, blocked: bool) -> Graph {
+        let mut edges: Vec<Edge> = Vec::new();
+        let mut positions: HashMap<usize, Vec3> = HashMap::new();
+        let included = |node_id: usize| blocked || grid.walkable(node_id);
+
+        for node_id in (0..grid.size).filter(|node_id| included(*node_id)) {
+            let coord = grid.coords(node_id);
+            positions.insert(node_id, Vec3::from(coord.0 as f32, coord.1 as f32, 0.0));
+
+            for direction in directions {
+                let dest_coord = direction.attempt_move(coord);
+                if grid.outside(dest_coord) {
+                    continue;
+                }
+
+                let dest_id = grid.node_id(dest_coord);
+                if dest_id != node_id && included(dest_id) {
+                    edges.push(Edge::from(edges.len(), node_id, dest_id, cost_model.weight(grid, node_id, dest_id)));
+                }
+            }
+        }
+
+        let mut graph = Graph::from(edges);
+        graph.add_nodes(positions.keys().cloned());
+        graph.offer_positions(positions);
+        return graph;
+    }
+
+    // Adds nodes without edges, e.g. for cells without neighbours. Known nodes are kept.
+    pub(crate) fn add_nodes(&mut self, node_ids: impl Iterator<Item=usize>) {
+        for node_id in node_ids {
+            self.nodes_lookup.entry(node_id).or_insert_with(|| Node::from(node_id, Vec::new()));
+        }
+        self.node_count = self.nodes_lookup.len();
+    }
+
     pub fn sorted_by_weight_asc(&self) -> Vec<Edge> {
         let mut sorted_edges = self.edges.clone();
         sorted_edges.sort_by(|edge1, edge2|
@@ -113,27 +170,127 @@ impl Graph {
     }
 }
 
-pub fn minimum_spanning(graph: Graph) -> Graph {
-    let edges = graph.sorted_by_weight_asc();
-    let mut union_find = UnionFind::from(graph.node_count);
-    let mut min_edges = Vec::new();
+// Only edges and positions are stored, the lookups are rebuilt when reading the graph back.
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct GraphRef<'a> {
+    edges: &'a Vec<Edge>,
+    positions: &'a Option<HashMap<usize, Vec3>>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct GraphData {
+    edges: Vec<Edge>,
+    positions: Option<HashMap<usize, Vec3>>,
+}
 
-    for edge in edges {
-        if !union_find.connected(edge.source, edge.destination) {
-            union_find.unify(edge.source, edge.destination);
-            min_edges.push(edge);
-        }
+#[cfg(feature = "serde")]
+impl Serialize for Graph {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return GraphRef { edges: &self.edges, positions: &self.node_position_lookup }.serialize(serializer);
     }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Graph {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Graph, D::Error> {
+        let data = GraphData::deserialize(deserializer)?;
+        let mut graph = Graph::from(data.edges);
+        graph.node_position_lookup = data.positions;
+        return Ok(graph);
+    }
+}
+
+pub fn minimum_spanning(graph: &Graph) -> Graph {
+    return spanning::kruskal(graph, Spanning::Minimum).to_graph();
+}
+
+pub fn maximum_spanning(graph: &Graph) -> Graph {
+    return spanning::kruskal(graph, Spanning::Maximum).to_graph();
+}
 
-    return Graph::from(min_edges);
+
+#[cfg(test)]
+use crate::components::{component_count, connected_components};
+#[cfg(test)]
+use crate::search::cost;
+
+#[cfg(test)]
+fn grid() -> Grid {
+    return Grid::from(&[
+        &[1.0, 1.0, 3.0],
+        &[cost::INFINITY, 2.0, 1.0],
+    ]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn graph_should_survive_serialization() {
+    let mut graph = Graph::from(Vec::from([Edge::from(4, 0, 1, 0.5), Edge::from(7, 1, 2, 1.5)]));
+    graph.offer_positions(HashMap::from([(0, Vec3::from(0.0, 1.0, 0.0)), (1, Vec3::from(2.0, 1.0, 0.0))]));
+
+    let json = serde_json::to_string(&graph).unwrap();
+    let restored: Graph = serde_json::from_str(&json).unwrap();
+
+    assert!(graph.edges == restored.edges);
+    assert_eq!(1.5, restored.edges_lookup[&7].weight);
+    assert_eq!(1, restored.nodes_lookup[&1].edges.len());
+    assert_eq!(&Vec3::from(2.0, 1.0, 0.0), restored.get_position(&1));
+}
+
+#[test]
+fn from_grid_should_skip_blocked_cells() {
+    let graph = Graph::from_grid(&grid(), &[Direction::Up, Direction::Down, Direction::Left, Direction::Right], CostModel::Destination);
+
+    assert_eq!(5, graph.node_count);
+    assert_eq!(10, graph.edges.len());
+    assert!(!graph.nodes_lookup.contains_key(&3));
+    assert_eq!(&Vec3::from(1.0, 2.0, 0.0), graph.get_position(&5));
 }
 
+#[test]
+fn from_grid_should_keep_cells_without_walkable_neighbours() {
+    let graph = Graph::from_grid(&Grid::from(&[&[1.0, cost::INFINITY, 1.0]]), &[Direction::Left, Direction::Right], CostModel::Destination);
+
+    assert!(graph.edges.is_empty());
+    assert_eq!(2, graph.node_count);
+    assert!(graph.nodes_lookup.contains_key(&0) && graph.nodes_lookup.contains_key(&2));
+}
+
+#[test]
+fn from_grid_should_have_unique_edge_indices() {
+    let graph = Graph::from_grid(&grid(), &[Direction::Up, Direction::Left, Direction::DownRight], CostModel::Destination);
+
+    // edges 0 -> 4 and 5 -> 4 enter the same cell
+    assert_eq!(graph.edges.len(), graph.edges_lookup.len());
+    assert_eq!(2, graph.edges.iter().filter(|edge| edge.destination == 4).count());
+}
+
+#[test]
+fn from_grid_should_apply_cost_model() {
+    let directions = [Direction::Right, Direction::DownRight];
+    let weights = |cost_model: CostModel| Graph::from_grid(&grid(), &directions, cost_model).nodes_lookup[&0].edges
+        .iter().map(|edge| edge.weight).collect::<Vec<f32>>();
+
+    assert_eq!(vec![1.0, 2.0], weights(CostModel::Destination));
+    assert_eq!(vec![1.0, 1.5], weights(CostModel::Mean));
+    assert_eq!(vec![1.0, 1.5 * 2.0_f32.sqrt()], weights(CostModel::Distance));
+}
+
+#[test]
+fn from_grid_should_work_with_graph_algorithms() {
+    let graph = Graph::from_grid(&grid(), &[Direction::Up, Direction::Down, Direction::Left, Direction::Right], CostModel::Mean);
+
+    assert_eq!(4, minimum_spanning(&graph).edges.len());
+    assert_eq!(1, component_count(&connected_components(&graph)));
+}
 
 #[test]
 fn mst_should_return_graph() {
     let edge = Edge::from(0, 0, 1, 0.5);
     let graph = Graph::from(Vec::from([edge]));
-    let min_graph = minimum_spanning(graph);
+    let min_graph = minimum_spanning(&graph);
 
     assert_eq!(1, min_graph.edges_lookup.keys().count());
     assert_eq!(2, min_graph.nodes_lookup.keys().count());
@@ -143,7 +300,7 @@ fn mst_should_return_graph() {
 fn mst_should_return_graph_with_source_node_having_one_edge() {
     let edge = Edge::from(0, 0, 1, 0.5);
     let graph = Graph::from(Vec::from([edge]));
-    let min_graph = minimum_spanning(graph);
+    let min_graph = minimum_spanning(&graph);
 
     let source_node = min_graph.nodes_lookup.get(&0).unwrap();
     assert_eq!(1, source_node.edges.to_vec().len());
@@ -163,7 +320,7 @@ fn mst_should_return_minimum_spanning_tree() {
 
 
     let graph = Graph::from(Vec::from([edge1, edge2, edge3, edge4, edge5, edge6, edge7]));
-    let min_graph = minimum_spanning(graph);
+    let min_graph = minimum_spanning(&graph);
 
     let mut total_cost: f32 = 0.0;
     for edge in min_graph.edges {
@@ -173,6 +330,32 @@ fn mst_should_return_minimum_spanning_tree() {
     assert_eq!(0.7142857143, total_cost);
 }
 
+#[test]
+fn mst_should_support_sparse_node_ids() {
+    let graph = Graph::from(Vec::from([
+        Edge::from(0, 7, 42, 2.0),
+        Edge::from(1, 42, 1000, 1.0),
+        Edge::from(2, 7, 1000, 0.5),
+    ]));
+    let min_graph = minimum_spanning(&graph);
+
+    assert_eq!(2, min_graph.edges.len());
+    assert_eq!(3, min_graph.node_count);
+}
+
+#[test]
+fn maximum_spanning_should_return_maximum_spanning_tree() {
+    let graph = Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 2.0),
+        Edge::from(1, 1, 2, 1.0),
+        Edge::from(2, 0, 2, 0.5),
+    ]));
+    let max_graph = maximum_spanning(&graph);
+
+    let total_cost: f32 = max_graph.edges.iter().map(|edge| edge.weight).sum();
+    assert_eq!(3.0, total_cost);
+}
+
 #[test]
 fn edge_from_should_construct_edge() {
     let edge = Edge::from(0, 2, 3, 0.5);
@@ -1,3 +1,13 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::search::cost;
+
+// Recorded changes kept at most. Beyond, the older half is dropped, as if cleared, so grids edited all the time do
+// not grow without bound.
+pub(crate) const MAX_CHANGES: usize = 1 << 16;
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Direction {
     Up,
     Down,
@@ -31,11 +41,104 @@ impl Direction {
     }
 }
 
+// Weight of a move between neighbouring cells, used when turning a grid into a graph.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CostModel {
+    // cost of the entered cell, like the grid searches
+    Destination,
+    // mean cost of both cells
+    Mean,
+    // mean cost of both cells times the step length, diagonal steps are longer
+    Distance,
+}
+
+impl CostModel {
+    pub fn weight(&self, grid: &Grid, from: usize, to: usize) -> f32 {
+        let mean = (grid.cost(from) + grid.cost(to)) / 2.0;
+        return match self {
+            CostModel::Destination => grid.cost(to),
+            CostModel::Mean => mean,
+            CostModel::Distance => {
+                let ((row1, col1), (row2, col2)) = (grid.coords(from), grid.coords(to));
+                if row1 != row2 && col1 != col2 { mean * 2.0_f32.sqrt() } else { mean }
+            }
+        };
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(try_from = "GridData"))]
 pub struct Grid {
     pub width: usize,
     pub height: usize,
     pub costs: Vec<Vec<f32>>,
     pub size: usize,
+    // the change history is not stored, a read grid starts at version 0
+    #[cfg_attr(feature = "serde", serde(skip))]
+    changes: Vec<CellChange>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dropped_changes: usize,
+}
+
+// Read grids are checked for matching dimensions, before they become a grid.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct GridData {
+    width: usize,
+    height: usize,
+    costs: Vec<Vec<f32>>,
+    size: usize,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<GridData> for Grid {
+    type Error = String;
+
+    fn try_from(data: GridData) -> Result<Grid, String> {
+        if data.width == 0 || data.height == 0 {
+            return Err(String::from("grid is empty"));
+        }
+        if data.width.checked_mul(data.height) != Some(data.size) || data.costs.len() != data.height {
+            return Err(format!("grid of {} rows is not {}x{} with size {}", data.costs.len(), data.height, data.width, data.size));
+        }
+        if let Some(row) = data.costs.iter().position(|row| row.len() != data.width) {
+            return Err(format!("row {row} is not {} cells wide", data.width));
+        }
+
+        return Ok(Grid { width: data.width, height: data.height, costs: data.costs, size: data.size, changes: Vec::new(), dropped_changes: 0 });
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CellChange {
+    pub node_id: usize,
+    pub coord: (usize, usize),
+    pub previous: f32,
+    pub cost: f32,
+}
+
+// Bounding box of changed cells, both corners are inclusive.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Region {
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+}
+
+impl Region {
+    pub fn around(changes: &[CellChange]) -> Option<Region> {
+        let first = changes.first()?.coord;
+
+        return Some(changes.iter().fold(Region { from: first, to: first }, |region, change| Region {
+            from: (region.from.0.min(change.coord.0), region.from.1.min(change.coord.1)),
+            to: (region.to.0.max(change.coord.0), region.to.1.max(change.coord.1)),
+        }));
+    }
+
+    pub fn contains(&self, coord: (usize, usize)) -> bool {
+        return self.from.0 <= coord.0 && coord.0 <= self.to.0 && self.from.1 <= coord.1 && coord.1 <= self.to.1;
+    }
 }
 
 impl Grid {
@@ -58,6 +161,8 @@ impl Grid {
             height,
             costs,
             size: width * height,
+            changes: Vec::new(),
+            dropped_changes: 0,
         };
     }
 
@@ -89,8 +194,170 @@ impl Grid {
         let (row, col) = self.coords(node_id);
         return self.costs[row][col];
     }
+
+    pub fn walkable(&self, node_id: usize) -> bool {
+        return self.cost(node_id) < cost::INFINITY;
+    }
+
+    // Every method below records the cells it changes, see changes_since. Only the latest changes are kept, call
+    // clear_changes once all changes are seen.
+    pub fn set_cost(&mut self, coord: (usize, usize), cost: f32) {
+        let node_id = self.node_id(coord);
+        let previous = self.costs[coord.0][coord.1];
+
+        if previous != cost {
+            self.costs[coord.0][coord.1] = cost;
+            self.changes.push(CellChange { node_id, coord, previous, cost });
+        }
+
+        if self.changes.len() > MAX_CHANGES {
+            let dropped = self.changes.len() - MAX_CHANGES / 2;
+            self.changes.drain(..dropped);
+            self.dropped_changes += dropped;
+        }
+    }
+
+    pub fn block(&mut self, coord: (usize, usize)) {
+        self.set_cost(coord, cost::INFINITY);
+    }
+
+    pub fn unblock(&mut self, coord: (usize, usize), cost: f32) {
+        self.set_cost(coord, cost);
+    }
+
+    // Both corners are inclusive, cells outside of the grid are ignored.
+    pub fn fill_rect(&mut self, from: (usize, usize), to: (usize, usize), cost: f32) {
+        for row in from.0..=to.0.min(self.height - 1) {
+            for col in from.1..=to.1.min(self.width - 1) {
+                self.set_cost((row, col), cost);
+            }
+        }
+    }
+
+    // Copies the stamp's costs with its upper left corner at origin, cells outside of the grid are ignored.
+    pub fn apply_stamp(&mut self, origin: (usize, usize), stamp: &[&[f32]]) {
+        for (row, values) in stamp.iter().enumerate() {
+            for (col, cost) in values.iter().enumerate() {
+                let coord = (origin.0 + row, origin.1 + col);
+                if self.within(coord) {
+                    self.set_cost(coord, *cost);
+                }
+            }
+        }
+    }
+
+    // Increases with every recorded change.
+    pub fn version(&self) -> usize {
+        return self.dropped_changes + self.changes.len();
+    }
+
+    // Changes recorded after the given version. None, if some of them were already cleared, so everything derived
+    // from the grid at that version has to be invalidated.
+    pub fn changes_since(&self, version: usize) -> Option<&[CellChange]> {
+        if version < self.dropped_changes {
+            return None;
+        }
+
+        return self.changes.get(version - self.dropped_changes..);
+    }
+
+    pub fn clear_changes(&mut self) {
+        self.dropped_changes += self.changes.len();
+        self.changes.clear();
+    }
+}
+
+
+#[cfg(test)]
+fn changing_grid() -> Grid {
+    return Grid::from(&[
+        &[1.0, 1.0, 1.0, 1.0],
+        &[1.0, 1.0, 1.0, 1.0],
+        &[1.0, 1.0, 1.0, 1.0],
+    ]);
+}
+
+#[test]
+fn set_cost_should_record_change() {
+    let mut grid = changing_grid();
+
+    grid.set_cost((1, 2), 3.0);
+    grid.set_cost((1, 2), 3.0);
+
+    assert_eq!(3.0, grid.costs[1][2]);
+    assert_eq!(1, grid.version());
+    assert_eq!(&[CellChange { node_id: 6, coord: (1, 2), previous: 1.0, cost: 3.0 }], grid.changes_since(0).unwrap());
+}
+
+#[test]
+fn block_and_unblock_should_change_walkability() {
+    let mut grid = changing_grid();
+
+    grid.block((0, 1));
+    assert!(!grid.walkable(1));
+
+    grid.unblock((0, 1), 2.0);
+    assert!(grid.walkable(1));
+    assert_eq!(2.0, grid.cost(1));
+    assert_eq!(2, grid.version());
+}
+
+#[test]
+fn fill_rect_should_clip_to_grid() {
+    let mut grid = changing_grid();
+
+    grid.fill_rect((1, 2), (5, 5), 4.0);
+
+    assert_eq!(4, grid.changes_since(0).unwrap().len());
+    assert_eq!(4.0, grid.costs[2][3]);
+    assert_eq!(1.0, grid.costs[0][2]);
+    assert_eq!(Some(Region { from: (1, 2), to: (2, 3) }), Region::around(grid.changes_since(0).unwrap()));
+}
+
+#[test]
+fn apply_stamp_should_copy_costs() {
+    let mut grid = changing_grid();
+
+    grid.apply_stamp((2, 2), &[
+        &[5.0, 6.0, 7.0],
+        &[8.0, 9.0, 10.0],
+    ]);
+
+    assert_eq!(vec![1.0, 1.0, 5.0, 6.0], grid.costs[2]);
+    assert_eq!(2, grid.version());
+}
+
+#[test]
+fn changes_since_should_report_cleared_changes() {
+    let mut grid = changing_grid();
+    grid.set_cost((0, 0), 2.0);
+    let version = grid.version();
+    grid.set_cost((0, 1), 2.0);
+
+    assert_eq!(1, grid.changes_since(version).unwrap().len());
+
+    grid.clear_changes();
+    grid.set_cost((0, 2), 2.0);
+
+    assert!(grid.changes_since(version).is_none());
+    assert_eq!(1, grid.changes_since(2).unwrap().len());
+    assert!(grid.changes_since(3).unwrap().is_empty());
+    assert!(Region::around(grid.changes_since(3).unwrap()).is_none());
 }
 
+#[test]
+fn changes_should_drop_oldest_beyond_limit() {
+    let mut grid = changing_grid();
+
+    for step in 0..=MAX_CHANGES {
+        grid.set_cost((0, 0), 2.0 + (step % 2) as f32);
+    }
+
+    assert_eq!(MAX_CHANGES + 1, grid.version());
+    assert!(grid.changes_since(0).is_none());
+    assert_eq!(MAX_CHANGES / 2, grid.changes_since(MAX_CHANGES / 2 + 1).unwrap().len());
+    assert_eq!(1, grid.changes_since(MAX_CHANGES).unwrap().len());
+}
 
 // Testing
 #[test]
@@ -212,6 +479,16 @@ fn get_cost_with_node_id_right_lower() {
     assert_eq!(7.0, grid.cost(8));
 }
 
+#[test]
+fn walkable_should_exclude_infinite_cost() {
+    let grid = Grid::from(&[
+        &[4.0, cost::INFINITY, 1.0]
+    ]);
+
+    assert!(grid.walkable(0));
+    assert!(!grid.walkable(1));
+}
+
 #[test]
 #[should_panic(expected = "Node id exceeds grid size")]
 fn get_cost_with_node_id_should_panic() {
@@ -343,3 +620,31 @@ fn from_with_no_rows_should_panic() {
 fn from_with_no_columns_should_panic() {
     Grid::from(&[&[]]);
 }
+
+#[cfg(feature = "serde")]
+#[test]
+fn grid_should_survive_serialization() {
+    let mut grid = changing_grid();
+    grid.block((1, 2));
+
+    let json = serde_json::to_string(&grid).unwrap();
+    let restored: Grid = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(grid.costs, restored.costs);
+    assert_eq!((grid.width, grid.height, grid.size), (restored.width, restored.height, restored.size));
+    assert!(!restored.walkable(restored.node_id((1, 2))));
+    assert_eq!(Some(&[][..]), restored.changes_since(0));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn grid_deserialization_should_check_dimensions() {
+    let read = |json: &str| serde_json::from_str::<Grid>(json).map(|_| ()).map_err(|error| error.to_string());
+
+    assert_eq!(Ok(()), read("{\"width\":2,\"height\":1,\"costs\":[[1.0,2.0]],\"size\":2}"));
+    assert!(read("{\"width\":2,\"height\":1,\"costs\":[[1.0,2.0]],\"size\":3}").is_err());
+    assert!(read("{\"width\":2,\"height\":2,\"costs\":[[1.0,2.0]],\"size\":4}").is_err());
+    assert!(read("{\"width\":2,\"height\":2,\"costs\":[[1.0,2.0],[1.0]],\"size\":4}").unwrap_err().starts_with("row 1"));
+    assert!(read("{\"width\":0,\"height\":0,\"costs\":[],\"size\":0}").is_err());
+    assert!(read("{\"width\":4294967296,\"height\":4294967296,\"costs\":[],\"size\":0}").is_err());
+}
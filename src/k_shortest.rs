@@ -0,0 +1,325 @@
+#[cfg(test)]
+use std::collections::HashSet;
+use std::collections::HashMap;
+
+use ordered_float::NotNan;
+use priority_queue::DoublePriorityQueue;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::graph::{Edge, Graph};
+use crate::query::QueryOptions;
+use crate::search::dijkstra;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum KShortest {
+    Yen,
+    Eppstein,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RankedPath {
+    pub cost: f32,
+    pub path: Graph,
+}
+
+pub fn k_shortest_paths(graph: &Graph, source: usize, target: usize, k: usize) -> Vec<RankedPath> {
+    return k_shortest_paths_with(graph, source, target, k, KShortest::Yen);
+}
+
+pub fn k_shortest_paths_with(graph: &Graph,
+                             source: usize,
+                             target: usize,
+                             k: usize,
+                             algorithm: KShortest) -> Vec<RankedPath> {
+    if k == 0 || !graph.nodes_lookup.contains_key(&source) || !graph.nodes_lookup.contains_key(&target) {
+        return Vec::new();
+    }
+
+    return match algorithm {
+        KShortest::Yen => yen(graph, source, target, k),
+        KShortest::Eppstein => eppstein(graph, source, target, k),
+    };
+}
+
+fn path_cost(edges: &[Edge]) -> f32 {
+    return edges.iter().map(|edge| edge.weight).sum();
+}
+
+fn shortest_path(graph: &Graph, source: usize, target: usize, options: &QueryOptions) -> Option<Vec<Edge>> {
+    let source_node = graph.nodes_lookup.get(&source)?;
+    let target_node = graph.nodes_lookup.get(&target)?;
+
+    let edges = dijkstra(source_node.clone(), target_node.clone(), graph, &|_| 0.0, options).edges;
+    if edges.is_empty() && source != target {
+        return None;
+    }
+
+    return Some(edges);
+}
+
+// Yen's algorithm, only returns loopless paths.
+fn yen(graph: &Graph, source: usize, target: usize, k: usize) -> Vec<RankedPath> {
+    let mut accepted: Vec<Vec<Edge>> = match shortest_path(graph, source, target, &QueryOptions::new()) {
+        None => return Vec::new(),
+        Some(edges) => vec![edges]
+    };
+    let mut candidates: Vec<Vec<Edge>> = Vec::new();
+
+    while accepted.len() < k {
+        let previous = accepted.last().unwrap().clone();
+        let mut previous_nodes = vec![source];
+        previous_nodes.extend(previous.iter().map(|edge| edge.destination));
+
+        for spur_position in 0..previous.len() {
+            let spur_node = previous_nodes[spur_position];
+            let root = &previous[..spur_position];
+
+            let options = QueryOptions {
+                blocked_edges: accepted.iter()
+                    .filter(|path| path.len() > spur_position && path[..spur_position] == *root)
+                    .map(|path| path[spur_position].index())
+                    .collect(),
+                blocked_nodes: previous_nodes[..spur_position].iter().cloned().collect(),
+                ..QueryOptions::new()
+            };
+
+            if let Some(spur_path) = shortest_path(graph, spur_node, target, &options) {
+                let mut candidate = root.to_vec();
+                candidate.extend(spur_path);
+
+                if !accepted.contains(&candidate) && !candidates.contains(&candidate) {
+                    candidates.push(candidate);
+                }
+            }
+        }
+
+        if candidates.is_empty() {
+            break;
+        }
+
+        let best = (0..candidates.len())
+            .min_by(|index1, index2| path_cost(&candidates[*index1]).total_cmp(&path_cost(&candidates[*index2])))
+            .unwrap();
+        accepted.push(candidates.swap_remove(best));
+    }
+
+    return accepted.into_iter().map(|edges| RankedPath {
+        cost: path_cost(&edges),
+        path: Graph::from(edges),
+    }).collect();
+}
+
+// Eppstein's algorithm. Every path is represented by the sidetracks, edges not part of the shortest path tree
+// towards the target, it takes. Paths may contain loops, but end at their first arrival at the target.
+// Instead of Eppstein's persistent heaps, the sidetracks of a path are expanded eagerly.
+fn eppstein(graph: &Graph, source: usize, target: usize, k: usize) -> Vec<RankedPath> {
+    let (distances, tree) = shortest_path_tree(graph, target);
+    let source_distance = match distances.get(&source) {
+        None => return Vec::new(),
+        Some(distance) => *distance
+    };
+
+    let mut states: Vec<Vec<Edge>> = vec![Vec::new()];
+    let mut queue: DoublePriorityQueue<usize, NotNan<f32>> = DoublePriorityQueue::new();
+    queue.push(0, NotNan::new(source_distance).unwrap());
+
+    let mut paths = Vec::new();
+
+    while let Some((state, cost)) = queue.pop_min() {
+        let sidetracks = states[state].clone();
+        let start = sidetracks.last().map_or(source, |edge| edge.destination);
+
+        let mut current = Some(start);
+        while let Some(node_id) = current {
+            if node_id == target {
+                break;
+            }
+
+            for edge in &graph.nodes_lookup[&node_id].edges {
+                let is_tree_edge = tree.get(&node_id).is_some_and(|tree_edge| tree_edge == edge);
+                if is_tree_edge {
+                    continue;
+                }
+
+                if let Some(destination_distance) = distances.get(&edge.destination) {
+                    let delta = edge.weight + destination_distance - distances[&node_id];
+                    let mut child = sidetracks.clone();
+                    child.push(edge.clone());
+
+                    queue.push(states.len(), NotNan::new(cost.into_inner() + delta).unwrap());
+                    states.push(child);
+                }
+            }
+
+            current = tree.get(&node_id).map(|edge| edge.destination);
+        }
+
+        paths.push(RankedPath {
+            cost: cost.into_inner(),
+            path: Graph::from(unfold(source, target, &sidetracks, &tree)),
+        });
+
+        if paths.len() == k {
+            break;
+        }
+    }
+
+    return paths;
+}
+
+fn unfold(source: usize, target: usize, sidetracks: &[Edge], tree: &HashMap<usize, Edge>) -> Vec<Edge> {
+    let mut edges = Vec::new();
+    let mut current = source;
+
+    for sidetrack in sidetracks {
+        while current != sidetrack.source {
+            let edge = &tree[&current];
+            edges.push(edge.clone());
+            current = edge.destination;
+        }
+
+        edges.push(sidetrack.clone());
+        current = sidetrack.destination;
+    }
+
+    while current != target {
+        let edge = &tree[&current];
+        edges.push(edge.clone());
+        current = edge.destination;
+    }
+
+    return edges;
+}
+
+// Shortest distances towards the target and, for each node, the first edge of its shortest path to the target.
+fn shortest_path_tree(graph: &Graph, target: usize) -> (HashMap<usize, f32>, HashMap<usize, Edge>) {
+    let mut incoming: HashMap<usize, Vec<&Edge>> = HashMap::new();
+    for edge in &graph.edges {
+        incoming.entry(edge.destination).or_default().push(edge);
+    }
+
+    let mut distances: HashMap<usize, f32> = HashMap::new();
+    let mut tree: HashMap<usize, Edge> = HashMap::new();
+    let mut queue: DoublePriorityQueue<usize, NotNan<f32>> = DoublePriorityQueue::new();
+    queue.push(target, NotNan::new(0.0).unwrap());
+
+    while let Some((node_id, distance)) = queue.pop_min() {
+        distances.insert(node_id, distance.into_inner());
+
+        for edge in incoming.get(&node_id).unwrap_or(&Vec::new()) {
+            if distances.contains_key(&edge.source) {
+                continue;
+            }
+
+            let cost = distance + edge.weight;
+            if queue.get_priority(&edge.source).is_some_and(|queued| *queued <= cost) {
+                continue;
+            }
+
+            queue.push(edge.source, cost);
+            tree.insert(edge.source, (*edge).clone());
+        }
+    }
+
+    return (distances, tree);
+}
+
+
+#[cfg(test)]
+fn graph() -> Graph {
+    // Yen's example network with nodes C=0, D=1, E=2, F=3, G=4, H=5
+    return Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 3.0),
+        Edge::from(1, 0, 2, 2.0),
+        Edge::from(2, 1, 3, 4.0),
+        Edge::from(3, 2, 1, 1.0),
+        Edge::from(4, 2, 3, 2.0),
+        Edge::from(5, 2, 4, 3.0),
+        Edge::from(6, 3, 4, 2.0),
+        Edge::from(7, 3, 5, 1.0),
+        Edge::from(8, 4, 5, 2.0),
+    ]));
+}
+
+#[cfg(test)]
+fn nodes(source: usize, path: &Graph) -> Vec<usize> {
+    let mut nodes = vec![source];
+    nodes.extend(path.edges.iter().map(|edge| edge.destination));
+    return nodes;
+}
+
+#[test]
+fn yen_should_return_ranked_paths() {
+    let paths = k_shortest_paths(&graph(), 0, 5, 3);
+
+    assert_eq!(3, paths.len());
+    assert_eq!(5.0, paths[0].cost);
+    assert_eq!(vec![0, 2, 3, 5], nodes(0, &paths[0].path));
+    assert_eq!(7.0, paths[1].cost);
+    assert_eq!(vec![0, 2, 4, 5], nodes(0, &paths[1].path));
+    assert_eq!(8.0, paths[2].cost);
+}
+
+#[test]
+fn yen_should_return_fewer_paths_if_exhausted() {
+    let graph = Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 1.0),
+        Edge::from(1, 1, 2, 1.0),
+        Edge::from(2, 0, 2, 5.0),
+    ]));
+
+    let paths = k_shortest_paths(&graph, 0, 2, 5);
+
+    assert_eq!(2, paths.len());
+    assert_eq!(2.0, paths[0].cost);
+    assert_eq!(5.0, paths[1].cost);
+}
+
+#[test]
+fn yen_paths_should_be_loopless() {
+    let mut edges = graph().edges;
+    edges.push(Edge::from(9, 1, 0, 1.0));
+
+    for ranked in k_shortest_paths(&Graph::from(edges), 0, 5, 10) {
+        let nodes = nodes(0, &ranked.path);
+        let unique: HashSet<&usize> = nodes.iter().collect();
+        assert_eq!(nodes.len(), unique.len());
+    }
+}
+
+#[test]
+fn k_shortest_paths_with_unreachable_target_should_be_empty() {
+    assert!(k_shortest_paths(&graph(), 5, 0, 3).is_empty());
+    assert!(k_shortest_paths_with(&graph(), 5, 0, 3, KShortest::Eppstein).is_empty());
+}
+
+#[test]
+fn eppstein_should_match_yen_on_acyclic_graph() {
+    let graph = graph();
+    let yen = k_shortest_paths_with(&graph, 0, 5, 6, KShortest::Yen);
+    let eppstein = k_shortest_paths_with(&graph, 0, 5, 6, KShortest::Eppstein);
+
+    let yen_costs: Vec<f32> = yen.iter().map(|ranked| ranked.cost).collect();
+    let eppstein_costs: Vec<f32> = eppstein.iter().map(|ranked| ranked.cost).collect();
+
+    assert_eq!(yen_costs, eppstein_costs);
+    for ranked in eppstein {
+        assert_eq!(ranked.cost, path_cost(&ranked.path.edges));
+        assert_eq!(5, ranked.path.edges.last().unwrap().destination);
+    }
+}
+
+#[test]
+fn eppstein_should_allow_loops() {
+    let graph = Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 1.0),
+        Edge::from(1, 1, 0, 1.0),
+        Edge::from(2, 1, 2, 1.0),
+    ]));
+
+    let paths = k_shortest_paths_with(&graph, 0, 2, 3, KShortest::Eppstein);
+
+    assert_eq!(vec![2.0, 4.0, 6.0], paths.iter().map(|ranked| ranked.cost).collect::<Vec<f32>>());
+}
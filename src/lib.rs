@@ -1,6 +1,27 @@
 pub mod path;
 pub mod graph;
 pub mod union_find;
+pub mod spanning;
+pub mod components;
+pub mod dag;
+pub mod biconnected;
+pub mod flow;
+pub mod k_shortest;
+pub mod time_dependent;
+pub mod pareto;
+pub mod query;
+pub mod contraction;
+pub mod navmesh;
+pub mod visibility;
+pub mod map;
+pub mod benchmark;
+pub mod formats;
+pub mod render;
+#[cfg(feature = "binary")]
+pub mod binary;
+pub mod cache;
+pub mod multi_agent;
+pub mod conflict_based;
 pub mod search;
 pub mod node;
 pub mod grid;
\ No newline at end of file
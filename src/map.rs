@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::grid::Grid;
+use crate::search::cost;
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MapError {
+    Io(String),
+    Empty,
+    // Missing or malformed header entry.
+    Header(String),
+    // Row with a different length than the first row.
+    RowLength(usize),
+    UnknownTile { tile: char, coord: (usize, usize) },
+    Image(String),
+    // Malformed line of a scenario file.
+    Scenario(usize),
+}
+
+// Moving AI benchmark maps. Ground and swamp are passable at cost 1, trees, water and out of bounds are blocked.
+pub fn moving_ai(text: &str) -> Result<Grid, MapError> {
+    let mut lines = text.lines();
+    let mut header: HashMap<&str, &str> = HashMap::new();
+
+    for line in lines.by_ref() {
+        let line = line.trim();
+        if line == "map" {
+            break;
+        }
+
+        let mut parts = line.split_whitespace();
+        if let (Some(key), Some(value)) = (parts.next(), parts.next()) {
+            header.insert(key, value);
+        }
+    }
+
+    let dimension = |key: &str| header.get(key).and_then(|value| value.parse::<usize>().ok())
+        .ok_or(MapError::Header(key.to_string()));
+    let (height, width) = (dimension("height")?, dimension("width")?);
+
+    let tiles = HashMap::from([
+        ('.', 1.0), ('G', 1.0), ('S', 1.0),
+        ('@', cost::INFINITY), ('O', cost::INFINITY), ('T', cost::INFINITY), ('W', cost::INFINITY),
+    ]);
+    let grid = ascii(&lines.collect::<Vec<&str>>().join("\n"), &tiles)?;
+
+    if grid.height != height || grid.width != width {
+        return Err(MapError::Header(format!("map is {}x{}, header says {height}x{width}", grid.height, grid.width)));
+    }
+
+    return Ok(grid);
+}
+
+// One row per line, every character is looked up in the cost table. Trailing empty lines are ignored.
+pub fn ascii(text: &str, costs: &HashMap<char, f32>) -> Result<Grid, MapError> {
+    let rows: Vec<&str> = text.trim_end_matches(['\n', '\r']).lines().collect();
+    let mut matrix: Vec<Vec<f32>> = Vec::new();
+
+    for (row, line) in rows.iter().enumerate() {
+        let mut costs_in_row = Vec::new();
+        for (col, tile) in line.trim_end_matches('\r').chars().enumerate() {
+            match costs.get(&tile) {
+                None => return Err(MapError::UnknownTile { tile, coord: (row, col) }),
+                Some(cost) => costs_in_row.push(*cost)
+            }
+        }
+        matrix.push(costs_in_row);
+    }
+
+    return to_grid(matrix);
+}
+
+// Binary (P5) or plain (P2) portable graymap. The mapping turns the brightness between 0 and 1 into a cost.
+pub fn pgm(bytes: &[u8], mapping: &dyn Fn(f32) -> f32) -> Result<Grid, MapError> {
+    let mut position = 0;
+    let mut token = || -> Result<String, MapError> {
+        // header tokens are separated by whitespace, comments run to the end of the line
+        loop {
+            match bytes.get(position) {
+                Some(b'#') => while bytes.get(position).is_some_and(|byte| *byte != b'\n') { position += 1; },
+                Some(byte) if byte.is_ascii_whitespace() => position += 1,
+                _ => break
+            }
+        }
+
+        let start = position;
+        while bytes.get(position).is_some_and(|byte| !byte.is_ascii_whitespace()) {
+            position += 1;
+        }
+
+        return String::from_utf8(bytes[start..position].to_vec()).map_err(|_| MapError::Header("token".to_string()));
+    };
+
+    let magic = token()?;
+    let number = |value: String, key: &str| value.parse::<usize>().map_err(|_| MapError::Header(key.to_string()));
+    let width = number(token()?, "width")?;
+    let height = number(token()?, "height")?;
+    let max = number(token()?, "maxval")?;
+    if max == 0 || max > 65535 {
+        return Err(MapError::Header("maxval".to_string()));
+    }
+    let size = width.checked_mul(height).ok_or(MapError::Header("width times height overflows".to_string()))?;
+
+    let values: Vec<usize> = match magic.as_str() {
+        "P2" => (0..size).map(|_| number(token()?, "value")).collect::<Result<Vec<usize>, MapError>>()?,
+        "P5" => {
+            // a single whitespace byte separates the header from the raster
+            let raster = bytes.get(position + 1..).unwrap_or(&[]);
+            let sample = if max > 255 { 2 } else { 1 };
+            if size.checked_mul(sample).is_none_or(|length| raster.len() < length) {
+                return Err(MapError::Image("raster is shorter than width times height".to_string()));
+            }
+
+            raster.chunks(sample).take(size)
+                .map(|chunk| chunk.iter().fold(0, |value, byte| value * 256 + *byte as usize))
+                .collect()
+        }
+        _ => return Err(MapError::Header("magic number".to_string()))
+    };
+
+    return to_grid(brightness_rows(&values, width, max as f32, mapping));
+}
+
+// Grayscale, RGB and palette images, colors are converted to their luma.
+#[cfg(feature = "png")]
+pub fn png(bytes: &[u8], mapping: &dyn Fn(f32) -> f32) -> Result<Grid, MapError> {
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let mut reader = decoder.read_info().map_err(|error| MapError::Image(error.to_string()))?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(|error| MapError::Image(error.to_string()))?;
+
+    let (sample, max) = match info.bit_depth {
+        png::BitDepth::Sixteen => (2, 65535.0),
+        _ => (1, 255.0)
+    };
+    let channels = info.color_type.samples();
+    let samples: Vec<f32> = buffer[..info.buffer_size()].chunks(sample)
+        .map(|chunk| chunk.iter().fold(0.0, |value, byte| value * 256.0 + *byte as f32))
+        .collect();
+
+    let values: Vec<usize> = samples.chunks(channels).map(|pixel| match info.color_type {
+        png::ColorType::Rgb | png::ColorType::Rgba => 0.299 * pixel[0] + 0.587 * pixel[1] + 0.114 * pixel[2],
+        _ => pixel[0]
+    }.round() as usize).collect();
+
+    return to_grid(brightness_rows(&values, info.width as usize, max, mapping));
+}
+
+pub fn load_moving_ai(path: impl AsRef<Path>) -> Result<Grid, MapError> {
+    return moving_ai(&fs::read_to_string(path).map_err(|error| MapError::Io(error.to_string()))?);
+}
+
+pub fn load_ascii(path: impl AsRef<Path>, costs: &HashMap<char, f32>) -> Result<Grid, MapError> {
+    return ascii(&fs::read_to_string(path).map_err(|error| MapError::Io(error.to_string()))?, costs);
+}
+
+pub fn load_pgm(path: impl AsRef<Path>, mapping: &dyn Fn(f32) -> f32) -> Result<Grid, MapError> {
+    return pgm(&fs::read(path).map_err(|error| MapError::Io(error.to_string()))?, mapping);
+}
+
+#[cfg(feature = "png")]
+pub fn load_png(path: impl AsRef<Path>, mapping: &dyn Fn(f32) -> f32) -> Result<Grid, MapError> {
+    return png(&fs::read(path).map_err(|error| MapError::Io(error.to_string()))?, mapping);
+}
+
+fn brightness_rows(values: &[usize], width: usize, max: f32, mapping: &dyn Fn(f32) -> f32) -> Vec<Vec<f32>> {
+    if width == 0 {
+        return Vec::new();
+    }
+
+    return values.chunks(width)
+        .map(|row| row.iter().map(|value| mapping(*value as f32 / max)).collect())
+        .collect();
+}
+
+fn to_grid(matrix: Vec<Vec<f32>>) -> Result<Grid, MapError> {
+    if matrix.is_empty() || matrix[0].is_empty() {
+        return Err(MapError::Empty);
+    }
+
+    if let Some(row) = matrix.iter().position(|row| row.len() != matrix[0].len()) {
+        return Err(MapError::RowLength(row));
+    }
+
+    let rows: Vec<&[f32]> = matrix.iter().map(|row| row.as_slice()).collect();
+    return Ok(Grid::from(&rows));
+}
+
+
+#[test]
+fn moving_ai_should_read_map() {
+    let grid = moving_ai("type octile\nheight 3\nwidth 4\nmap\n..@.\n.TW.\nGS..\n").unwrap();
+
+    assert_eq!((3, 4), (grid.height, grid.width));
+    assert_eq!(1.0, grid.costs[2][1]);
+    assert!(!grid.walkable(grid.node_id((0, 2))));
+    assert!(!grid.walkable(grid.node_id((1, 1))));
+    assert!(!grid.walkable(grid.node_id((1, 2))));
+}
+
+#[test]
+fn moving_ai_should_check_header() {
+    assert_eq!(Some(MapError::Header("width".to_string())), moving_ai("type octile\nheight 1\nmap\n..\n").err());
+    assert!(matches!(moving_ai("type octile\nheight 2\nwidth 2\nmap\n..\n"), Err(MapError::Header(_))));
+}
+
+#[test]
+fn ascii_should_use_cost_table() {
+    let costs = HashMap::from([('.', 1.0), ('~', 3.0), ('#', cost::INFINITY)]);
+
+    let grid = ascii("..~\r\n#~.\n\n", &costs).unwrap();
+
+    assert_eq!(vec![vec![1.0, 1.0, 3.0], vec![cost::INFINITY, 3.0, 1.0]], grid.costs);
+    assert_eq!(Some(MapError::UnknownTile { tile: 'x', coord: (1, 0) }), ascii("..\nx.", &costs).err());
+    assert_eq!(Some(MapError::RowLength(1)), ascii("..\n.", &costs).err());
+    assert_eq!(Some(MapError::Empty), ascii("", &costs).err());
+}
+
+#[test]
+fn pgm_should_map_brightness_to_cost() {
+    let mut binary = b"P5\n# exported\n3 2\n255\n".to_vec();
+    binary.extend([0, 51, 255, 255, 102, 0]);
+    let mapping = |brightness: f32| if brightness == 0.0 { cost::INFINITY } else { (brightness * 5.0).round() };
+
+    let grid = pgm(&binary, &mapping).unwrap();
+    let plain = pgm(b"P2 3 2 255\n0 51 255\n255 102 0\n", &mapping).unwrap();
+
+    assert_eq!(vec![vec![cost::INFINITY, 1.0, 5.0], vec![5.0, 2.0, cost::INFINITY]], grid.costs);
+    assert_eq!(grid.costs, plain.costs);
+}
+
+#[test]
+fn pgm_should_reject_broken_images() {
+    assert_eq!(Some(MapError::Header("magic number".to_string())), pgm(b"P6 1 1 255\n\0", &|value| value).err());
+    assert!(matches!(pgm(b"P5 2 2 255\n\0\0", &|value| value), Err(MapError::Image(_))));
+    assert!(matches!(pgm(b"P5 4294967296 4294967296 255\n\0", &|value| value), Err(MapError::Header(_))));
+    assert!(matches!(pgm(b"P5 4294967296 1 65535\n\0", &|value| value), Err(MapError::Image(_))));
+    assert!(matches!(pgm(b"P2 4294967296 4294967296 255\n0", &|value| value), Err(MapError::Header(_))));
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn png_should_map_brightness_to_cost() {
+    let mut bytes = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut bytes, 2, 2);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header().unwrap().write_image_data(&[0, 255, 51, 102]).unwrap();
+    }
+
+    let grid = png(&bytes, &|brightness| brightness * 5.0).unwrap();
+
+    assert_eq!(vec![vec![0.0, 5.0], vec![1.0, 2.0]], grid.costs);
+}
@@ -0,0 +1,419 @@
+use std::collections::{HashMap, HashSet};
+
+use ordered_float::NotNan;
+use priority_queue::DoublePriorityQueue;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::graph::Graph;
+use crate::grid::{CostModel, Direction, Grid};
+use crate::search::adjacency::{adjacency, distances, Adjacency};
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Agent {
+    pub source: (usize, usize),
+    pub target: (usize, usize),
+}
+
+// The node id an agent occupies at every time step, it stays at the last node afterwards.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimedPath {
+    pub steps: Vec<usize>,
+    pub cost: f32,
+}
+
+impl TimedPath {
+    pub fn at(&self, time: usize) -> usize {
+        return self.steps[time.min(self.steps.len() - 1)];
+    }
+
+    pub fn arrival(&self) -> usize {
+        return self.steps.len() - 1;
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MultiAgentPlan {
+    pub paths: Vec<TimedPath>,
+    pub sum_of_costs: f32,
+}
+
+impl MultiAgentPlan {
+    pub fn from(paths: Vec<TimedPath>) -> MultiAgentPlan {
+        let sum_of_costs = paths.iter().map(|path| path.cost).sum();
+        return MultiAgentPlan { paths, sum_of_costs };
+    }
+
+    pub fn makespan(&self) -> usize {
+        return self.paths.iter().map(|path| path.arrival()).max().unwrap_or(0);
+    }
+}
+
+// An edge conflict means the agents swap their nodes between time and time + 1.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Conflict {
+    Vertex { agents: (usize, usize), node_id: usize, time: usize },
+    Edge { agents: (usize, usize), from: usize, to: usize, time: usize },
+}
+
+pub fn first_conflict(paths: &[TimedPath]) -> Option<Conflict> {
+    return conflicts(paths).first().cloned();
+}
+
+// All vertex and edge conflicts, ordered by time.
+pub fn conflicts(paths: &[TimedPath]) -> Vec<Conflict> {
+    let makespan = paths.iter().map(|path| path.arrival()).max().unwrap_or(0);
+    let mut conflicts = Vec::new();
+
+    for time in 0..=makespan {
+        for agent1 in 0..paths.len() {
+            for agent2 in agent1 + 1..paths.len() {
+                let (path1, path2) = (&paths[agent1], &paths[agent2]);
+
+                if path1.at(time) == path2.at(time) {
+                    conflicts.push(Conflict::Vertex { agents: (agent1, agent2), node_id: path1.at(time), time });
+                }
+
+                let (from, to) = (path1.at(time), path1.at(time + 1));
+                if from != to && path2.at(time) == to && path2.at(time + 1) == from {
+                    conflicts.push(Conflict::Edge { agents: (agent1, agent2), from, to, time });
+                }
+            }
+        }
+    }
+
+    return conflicts;
+}
+
+// Nodes and moves already taken by agents at given times.
+#[derive(Default)]
+pub struct ReservationTable {
+    vertices: HashMap<(usize, usize), usize>,
+    edges: HashMap<(usize, usize, usize), usize>,
+}
+
+impl ReservationTable {
+    pub fn new() -> ReservationTable {
+        return ReservationTable::default();
+    }
+
+    // Reserves the nodes of the steps, starting at the given time.
+    pub fn reserve(&mut self, agent: usize, start_time: usize, steps: &[usize]) {
+        for (offset, node_id) in steps.iter().enumerate() {
+            self.vertices.insert((*node_id, start_time + offset), agent);
+
+            if let Some(next) = steps.get(offset + 1) {
+                self.edges.insert((*node_id, *next, start_time + offset), agent);
+            }
+        }
+    }
+
+    pub fn reserved_by(&self, node_id: usize, time: usize) -> Option<usize> {
+        return self.vertices.get(&(node_id, time)).cloned();
+    }
+
+    // Whether an agent may move from one node to another between time and time + 1.
+    pub fn permits(&self, agent: usize, from: usize, to: usize, time: usize) -> bool {
+        let other = |reserved: Option<&usize>| reserved.is_some_and(|owner| *owner != agent);
+
+        return !other(self.vertices.get(&(to, time + 1)))
+            && !other(self.edges.get(&(to, from, time)));
+    }
+}
+
+// Windowed Hierarchical Cooperative A*. Agents plan one after another through space and time, avoiding the moves
+// reserved by the agents before them within the window. Beyond the window, the true distance to the target, which
+// ignores other agents, is used as heuristic. After every half window, all agents plan again from where they are.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CooperativeAStar {
+    pub window: usize,
+    pub max_steps: usize,
+}
+
+impl CooperativeAStar {
+    // None if an agent cannot reach its target, or not all agents arrived within the maximum number of steps.
+    // Waiting costs the cost of the current cell, except at the own target.
+    pub fn grid(&self, grid: &Grid, directions: &[Direction], agents: &[Agent]) -> Option<MultiAgentPlan> {
+        let (walkable, backward) = adjacency(&Graph::from_grid(grid, directions, CostModel::Destination));
+        let wait_cost = |target: usize, node_id: usize| if node_id == target { 0.0 } else { grid.cost(node_id) };
+
+        let targets: Vec<usize> = agents.iter().map(|agent| grid.node_id(agent.target)).collect();
+        let heuristics: Vec<HashMap<usize, f32>> = targets.iter().map(|target| distances(*target, &backward)).collect();
+        let mut steps: Vec<Vec<usize>> = agents.iter().map(|agent| vec![grid.node_id(agent.source)]).collect();
+
+        // the heuristic holds the distance of every node the target can be reached from
+        if (0..agents.len()).any(|agent| !grid.walkable(targets[agent]) || !heuristics[agent].contains_key(&steps[agent][0])) {
+            return None;
+        }
+
+        let window = self.window.max(1);
+        let advance = (window / 2).max(1);
+        let mut time = 0;
+
+        while (0..agents.len()).any(|agent| *steps[agent].last().unwrap() != targets[agent]) {
+            if time >= self.max_steps {
+                return None;
+            }
+
+            let mut table = ReservationTable::new();
+            let mut windows = Vec::new();
+
+            for agent in 0..agents.len() {
+                let target = targets[agent];
+                let search = SpaceTimeSearch {
+                    agent,
+                    adjacency: &walkable,
+                    heuristic: &heuristics[agent],
+                    wait_cost: &|node_id| wait_cost(target, node_id),
+                    table: &table,
+                };
+
+                let planned = search.window(*steps[agent].last().unwrap(), time, window)?;
+                table.reserve(agent, time, &planned);
+                windows.push(planned);
+            }
+
+            for (agent, planned) in windows.iter().enumerate() {
+                steps[agent].extend_from_slice(&planned[1..=advance]);
+            }
+            time += advance;
+        }
+
+        let paths = steps.into_iter().enumerate()
+            .map(|(agent, steps)| timed_path(steps, targets[agent], &|from, to| {
+                return if from == to { wait_cost(targets[agent], from) } else { grid.cost(to) };
+            }))
+            .collect();
+
+        return Some(MultiAgentPlan::from(paths));
+    }
+}
+
+// Drops the waiting at the target after the final arrival.
+pub(crate) fn timed_path(mut steps: Vec<usize>, target: usize, cost: &dyn Fn(usize, usize) -> f32) -> TimedPath {
+    while steps.len() > 1 && steps[steps.len() - 1] == target && steps[steps.len() - 2] == target {
+        steps.pop();
+    }
+
+    let cost = steps.windows(2).map(|step| cost(step[0], step[1])).sum();
+    return TimedPath { steps, cost };
+}
+
+pub(crate) struct SpaceTimeSearch<'a> {
+    pub agent: usize,
+    pub adjacency: &'a Adjacency,
+    pub heuristic: &'a HashMap<usize, f32>,
+    pub wait_cost: &'a dyn Fn(usize) -> f32,
+    pub table: &'a ReservationTable,
+}
+
+// Node at a time step, linked to the state it was reached from. Conflicts are only counted by conflict-based search.
+pub(crate) struct State {
+    pub node_id: usize,
+    pub time: usize,
+    pub cost: f32,
+    pub conflicts: usize,
+    pub previous: Option<usize>,
+}
+
+impl SpaceTimeSearch<'_> {
+    // Best nodes for the times start_time up to start_time + window, where the remaining distance to the target
+    // is estimated after the window.
+    pub fn window(&self, source: usize, start_time: usize, window: usize) -> Option<Vec<usize>> {
+        let end = start_time + window;
+        let mut states = vec![State { node_id: source, time: start_time, cost: 0.0, conflicts: 0, previous: None }];
+        let mut best: HashMap<(usize, usize), f32> = HashMap::new();
+        let mut closed: HashSet<(usize, usize)> = HashSet::new();
+        let mut queue: DoublePriorityQueue<usize, NotNan<f32>> = DoublePriorityQueue::new();
+
+        queue.push(0, NotNan::new(*self.heuristic.get(&source)?).unwrap());
+
+        while let Some((current, _)) = queue.pop_min() {
+            let (node_id, time, cost) = (states[current].node_id, states[current].time, states[current].cost);
+
+            if time == end {
+                return Some(walk_back(&states, current));
+            }
+            if !closed.insert((node_id, time)) {
+                continue;
+            }
+
+            let waiting = (node_id, (self.wait_cost)(node_id));
+            let moves = self.adjacency.get(&node_id).map_or(&[][..], |neighbours| &neighbours[..]);
+
+            for (next, step_cost) in moves.iter().cloned().chain([waiting]) {
+                let estimate = match self.heuristic.get(&next) {
+                    None => continue,
+                    Some(estimate) => *estimate
+                };
+
+                if !self.table.permits(self.agent, node_id, next, time) {
+                    continue;
+                }
+
+                let next_cost = cost + step_cost;
+                if best.get(&(next, time + 1)).is_some_and(|known| *known <= next_cost) {
+                    continue;
+                }
+
+                best.insert((next, time + 1), next_cost);
+                queue.push(states.len(), NotNan::new(next_cost + estimate).unwrap());
+                states.push(State { node_id: next, time: time + 1, cost: next_cost, conflicts: 0, previous: Some(current) });
+            }
+        }
+
+        return None;
+    }
+}
+
+pub(crate) fn walk_back(states: &[State], last: usize) -> Vec<usize> {
+    let mut steps = Vec::new();
+    let mut current = Some(last);
+
+    while let Some(state) = current {
+        steps.push(states[state].node_id);
+        current = states[state].previous;
+    }
+
+    steps.reverse();
+    return steps;
+}
+
+
+#[cfg(test)]
+pub(crate) fn straight() -> Vec<Direction> {
+    return vec![Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+}
+
+#[cfg(test)]
+pub(crate) fn corridor() -> Grid {
+    // a corridor with a single side pocket below the center
+    let blocked = crate::search::cost::INFINITY;
+    return Grid::from(&[
+        &[1.0, 1.0, 1.0, 1.0, 1.0],
+        &[blocked, blocked, 1.0, blocked, blocked],
+    ]);
+}
+
+#[test]
+fn first_conflict_should_detect_vertex_and_swap_conflicts() {
+    let path = |steps: Vec<usize>| TimedPath { cost: (steps.len() - 1) as f32, steps };
+
+    let vertex = first_conflict(&[path(vec![0, 1, 2]), path(vec![3, 2])]);
+    let swap = first_conflict(&[path(vec![0, 1, 2]), path(vec![2, 1, 0])]);
+    let crossing = first_conflict(&[path(vec![0, 1]), path(vec![1, 0])]);
+    let free = first_conflict(&[path(vec![0, 1, 2]), path(vec![3, 4])]);
+
+    assert_eq!(Some(Conflict::Vertex { agents: (0, 1), node_id: 2, time: 2 }), vertex);
+    assert_eq!(Some(Conflict::Vertex { agents: (0, 1), node_id: 1, time: 1 }), swap);
+    assert_eq!(Some(Conflict::Edge { agents: (0, 1), from: 0, to: 1, time: 0 }), crossing);
+    assert_eq!(None, free);
+}
+
+#[test]
+fn conflicts_should_list_all_conflicts() {
+    let path = |steps: Vec<usize>| TimedPath { cost: (steps.len() - 1) as f32, steps };
+
+    let found = conflicts(&[path(vec![0, 1, 2]), path(vec![1, 0, 2])]);
+
+    assert_eq!(vec![
+        Conflict::Edge { agents: (0, 1), from: 0, to: 1, time: 0 },
+        Conflict::Vertex { agents: (0, 1), node_id: 2, time: 2 },
+    ], found);
+}
+
+#[test]
+fn reservation_table_should_block_other_agents() {
+    let mut table = ReservationTable::new();
+    table.reserve(0, 2, &[5, 6]);
+
+    assert_eq!(Some(0), table.reserved_by(6, 3));
+    assert!(!table.permits(1, 4, 5, 1));
+    assert!(!table.permits(1, 6, 5, 2));
+    assert!(table.permits(1, 6, 7, 2));
+    assert!(table.permits(0, 4, 5, 1));
+}
+
+#[test]
+fn cooperative_a_star_should_plan_independent_agents_optimally() {
+    let grid = Grid::from(&[
+        &[1.0, 1.0, 1.0],
+        &[1.0, 1.0, 1.0],
+        &[1.0, 1.0, 1.0],
+    ]);
+    let agents = [
+        Agent { source: (0, 0), target: (0, 2) },
+        Agent { source: (2, 0), target: (2, 2) },
+    ];
+
+    let plan = CooperativeAStar { window: 4, max_steps: 20 }.grid(&grid, &straight(), &agents).unwrap();
+
+    assert_eq!(4.0, plan.sum_of_costs);
+    assert_eq!(2, plan.makespan());
+    assert_eq!(vec![0, 1, 2], plan.paths[0].steps);
+}
+
+#[test]
+fn cooperative_a_star_should_let_agents_pass_in_corridor() {
+    let grid = corridor();
+    let agents = [
+        Agent { source: (0, 0), target: (0, 4) },
+        Agent { source: (0, 3), target: (0, 0) },
+    ];
+
+    let plan = CooperativeAStar { window: 8, max_steps: 40 }.grid(&grid, &straight(), &agents).unwrap();
+
+    assert_eq!(None, first_conflict(&plan.paths));
+    assert_eq!(4, *plan.paths[0].steps.last().unwrap());
+    assert_eq!(0, *plan.paths[1].steps.last().unwrap());
+    assert!(plan.paths.iter().any(|path| path.steps.contains(&7)));
+}
+
+#[test]
+fn cooperative_a_star_should_let_agents_wait() {
+    let grid = corridor();
+    let agents = [
+        Agent { source: (0, 1), target: (1, 2) },
+        Agent { source: (0, 3), target: (0, 1) },
+    ];
+
+    let plan = CooperativeAStar { window: 6, max_steps: 20 }.grid(&grid, &straight(), &agents).unwrap();
+
+    assert_eq!(None, first_conflict(&plan.paths));
+    assert_eq!(vec![1, 2, 7], plan.paths[0].steps);
+    assert_eq!(3, plan.paths[1].arrival());
+    assert_eq!(plan.paths[1].steps[0], plan.paths[1].steps[1]);
+}
+
+#[test]
+fn cooperative_a_star_should_fail_for_unreachable_target() {
+    let grid = corridor();
+    let agents = [Agent { source: (0, 0), target: (1, 0) }];
+
+    assert!(CooperativeAStar { window: 4, max_steps: 20 }.grid(&grid, &straight(), &agents).is_none());
+}
+
+#[test]
+fn cooperative_a_star_should_fail_early_for_walled_off_target() {
+    let grid = Grid::from(&[&[1.0, crate::search::cost::INFINITY, 1.0]]);
+    let agents = [Agent { source: (0, 0), target: (0, 2) }];
+
+    assert!(CooperativeAStar { window: 4, max_steps: usize::MAX }.grid(&grid, &straight(), &agents).is_none());
+}
+
+#[test]
+fn cooperative_a_star_should_not_charge_waiting_at_own_target() {
+    let grid = corridor();
+    let agents = [
+        Agent { source: (0, 4), target: (0, 0) },
+        Agent { source: (0, 2), target: (0, 2) },
+    ];
+
+    let plan = CooperativeAStar { window: 6, max_steps: 20 }.grid(&grid, &straight(), &agents).unwrap();
+
+    assert_eq!(None, first_conflict(&plan.paths));
+    assert_eq!(vec![2, 2, 7, 2], plan.paths[1].steps);
+    assert_eq!(2.0, plan.paths[1].cost);
+}
@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::graph::{Edge, Graph};
+use crate::node::Vec3;
+use crate::path;
+use crate::search::{AStar, euclidean_distance};
+
+const EPSILON: f32 = 1e-5;
+
+// Passage from one polygon into a neighbouring one, left and right as seen when moving through it.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Portal {
+    pub from: usize,
+    pub to: usize,
+    pub left: Vec3,
+    pub right: Vec3,
+}
+
+// Convex polygons over the x-y plane, z being the height. Polygons are neighbours, if they share a side or a part of
+// it, as at a T-junction. Polygons stacked above each other are told apart by their height, the funnel works on the
+// x-y projection of the corridor.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NavMesh {
+    pub polygons: Vec<Vec<Vec3>>,
+    pub portals: Vec<Portal>,
+}
+
+impl NavMesh {
+    pub fn from(polygons: Vec<Vec<Vec3>>) -> NavMesh {
+        let polygons: Vec<Vec<Vec3>> = polygons.into_iter().map(counter_clockwise).collect();
+        let mut sides: HashMap<(Vec3, Vec3), usize> = HashMap::new();
+
+        for (polygon, vertices) in polygons.iter().enumerate() {
+            for (start, end) in sides_of(vertices) {
+                sides.insert((start, end), polygon);
+            }
+        }
+
+        // Counter-clockwise neighbours run along their shared side in opposite directions, the interior is on the left.
+        let mut portals = Vec::new();
+        let mut unmatched = Vec::new();
+        for (polygon, vertices) in polygons.iter().enumerate() {
+            for (start, end) in sides_of(vertices) {
+                match sides.get(&(end.clone(), start.clone())) {
+                    Some(neighbour) => portals.push(Portal { from: polygon, to: *neighbour, left: end, right: start }),
+                    None => unmatched.push((polygon, start, end))
+                }
+            }
+        }
+
+        // sides sharing only a part, compared pairwise
+        for (polygon, start, end) in &unmatched {
+            for (neighbour, other_start, other_end) in &unmatched {
+                if polygon == neighbour {
+                    continue;
+                }
+
+                if let Some((left, right)) = overlap(start, end, other_start, other_end) {
+                    portals.push(Portal { from: *polygon, to: *neighbour, left, right });
+                }
+            }
+        }
+
+        return NavMesh { polygons, portals };
+    }
+
+    pub fn centroid(&self, polygon: usize) -> Vec3 {
+        let vertices = &self.polygons[polygon];
+        let count = vertices.len() as f32;
+
+        return Vec3::from(vertices.iter().map(|vertex| vertex.x).sum::<f32>() / count,
+                          vertices.iter().map(|vertex| vertex.y).sum::<f32>() / count,
+                          vertices.iter().map(|vertex| vertex.z).sum::<f32>() / count);
+    }
+
+    // Polygon graph, the index of an edge is the index of its portal. Node positions are the centroids.
+    pub fn graph(&self) -> Graph {
+        let mut graph = Graph::from(self.portals.iter().enumerate()
+            .map(|(index, portal)| Edge::from(index, portal.from, portal.to,
+                                              self.centroid(portal.from).euclidean_dist(&self.centroid(portal.to))))
+            .collect());
+
+        graph.offer_positions((0..self.polygons.len()).map(|polygon| (polygon, self.centroid(polygon))).collect());
+        return graph;
+    }
+
+    // Of the polygons containing the point in the x-y plane, the one nearest in height.
+    pub fn locate(&self, point: &Vec3) -> Option<usize> {
+        return (0..self.polygons.len())
+            .filter(|polygon| sides_of(&self.polygons[*polygon]).iter().all(|(start, end)| cross(start, end, point) >= 0.0))
+            .min_by(|polygon1, polygon2| (self.height(*polygon1, point) - point.z).abs()
+                .total_cmp(&(self.height(*polygon2, point) - point.z).abs()));
+    }
+
+    // Height of the polygon's plane at the point, the plane's normal is found with Newell's method.
+    fn height(&self, polygon: usize, point: &Vec3) -> f32 {
+        let centroid = self.centroid(polygon);
+        let (mut normal_x, mut normal_y, mut normal_z) = (0.0, 0.0, 0.0);
+        for (start, end) in sides_of(&self.polygons[polygon]) {
+            normal_x += (start.y - end.y) * (start.z + end.z);
+            normal_y += (start.z - end.z) * (start.x + end.x);
+            normal_z += (start.x - end.x) * (start.y + end.y);
+        }
+
+        if normal_z.abs() <= EPSILON {
+            return centroid.z;
+        }
+        return centroid.z - (normal_x * (point.x - centroid.x) + normal_y * (point.y - centroid.y)) / normal_z;
+    }
+
+    // Polygons passed from the start polygon to the goal polygon, found with A* between the centroids.
+    pub fn corridor(&self, start: usize, goal: usize) -> Option<Vec<usize>> {
+        if start == goal {
+            return Some(vec![start]);
+        }
+
+        let graph = self.graph();
+        let found = path::in_graph(start, goal, &graph, Box::from(AStar { heuristic: Box::from(euclidean_distance) }));
+        if found.edges.is_empty() {
+            return None;
+        }
+
+        let mut corridor = vec![start];
+        corridor.extend(found.edges.iter().map(|edge| edge.destination));
+        return Some(corridor);
+    }
+
+    // Shortest path through the corridor with the funnel algorithm, from start to goal including both.
+    pub fn find_path(&self, start: &Vec3, goal: &Vec3) -> Option<Vec<Vec3>> {
+        let corridor = self.corridor(self.locate(start)?, self.locate(goal)?)?;
+        return self.funnel(start, goal, &corridor);
+    }
+
+    // None, if two polygons following each other in the corridor are not neighbours.
+    pub fn funnel(&self, start: &Vec3, goal: &Vec3, corridor: &[usize]) -> Option<Vec<Vec3>> {
+        let mut portals = vec![(start.clone(), start.clone())];
+        for pair in corridor.windows(2) {
+            let portal = self.portals.iter().find(|portal| portal.from == pair[0] && portal.to == pair[1])?;
+            portals.push((portal.left.clone(), portal.right.clone()));
+        }
+        portals.push((goal.clone(), goal.clone()));
+
+        let mut points = vec![start.clone()];
+        let (mut apex, mut left, mut right) = (start.clone(), start.clone(), start.clone());
+        let (mut left_index, mut right_index) = (0, 0);
+        let mut index = 1;
+
+        while index < portals.len() {
+            let (next_left, next_right) = &portals[index];
+
+            // tighten the right side, unless it crosses the left side
+            if cross(&apex, &right, next_right) >= 0.0 {
+                if apex == right || cross(&apex, &left, next_right) < 0.0 {
+                    right = next_right.clone();
+                    right_index = index;
+                } else {
+                    points.push(left.clone());
+                    apex = left.clone();
+                    right = apex.clone();
+                    right_index = left_index;
+                    index = left_index + 1;
+                    continue;
+                }
+            }
+
+            // tighten the left side, unless it crosses the right side
+            if cross(&apex, &left, next_left) <= 0.0 {
+                if apex == left || cross(&apex, &right, next_left) > 0.0 {
+                    left = next_left.clone();
+                    left_index = index;
+                } else {
+                    points.push(right.clone());
+                    apex = right.clone();
+                    left = apex.clone();
+                    left_index = right_index;
+                    index = right_index + 1;
+                    continue;
+                }
+            }
+
+            index += 1;
+        }
+
+        if points.last() != Some(goal) {
+            points.push(goal.clone());
+        }
+        return Some(points);
+    }
+}
+
+// Positive, if the point is left of the line from start to end.
+fn cross(start: &Vec3, end: &Vec3, point: &Vec3) -> f32 {
+    return (end.x - start.x) * (point.y - start.y) - (end.y - start.y) * (point.x - start.x);
+}
+
+// Part of the side from start to end, which the other side runs along in the opposite direction, as left and right.
+fn overlap(start: &Vec3, end: &Vec3, other_start: &Vec3, other_end: &Vec3) -> Option<(Vec3, Vec3)> {
+    let direction = (end.x - start.x, end.y - start.y, end.z - start.z);
+    let length_squared = direction.0 * direction.0 + direction.1 * direction.1 + direction.2 * direction.2;
+    if length_squared <= EPSILON {
+        return None;
+    }
+
+    let at = |share: f32| Vec3::from(start.x + share * direction.0, start.y + share * direction.1, start.z + share * direction.2);
+    let along = |point: &Vec3| ((point.x - start.x) * direction.0 + (point.y - start.y) * direction.1
+        + (point.z - start.z) * direction.2) / length_squared;
+
+    let (low, high) = (along(other_end), along(other_start));
+    let on_line = |point: &Vec3| at(along(point)).euclidean_dist(point) <= EPSILON;
+    if low >= high || !on_line(other_start) || !on_line(other_end) {
+        return None;
+    }
+
+    let (low, high) = (low.max(0.0), high.min(1.0));
+    if (high - low) * length_squared.sqrt() <= EPSILON {
+        return None;
+    }
+
+    return Some((at(high), at(low)));
+}
+
+fn sides_of(vertices: &[Vec3]) -> Vec<(Vec3, Vec3)> {
+    return (0..vertices.len())
+        .map(|index| (vertices[index].clone(), vertices[(index + 1) % vertices.len()].clone()))
+        .collect();
+}
+
+fn counter_clockwise(mut vertices: Vec<Vec3>) -> Vec<Vec3> {
+    let area: f32 = sides_of(&vertices).iter().map(|(start, end)| start.x * end.y - end.x * start.y).sum();
+    if area < 0.0 {
+        vertices.reverse();
+    }
+
+    return vertices;
+}
+
+
+#[cfg(test)]
+fn square(x: f32, y: f32) -> Vec<Vec3> {
+    return vec![
+        Vec3::from(x, y, 0.0),
+        Vec3::from(x + 1.0, y, 0.0),
+        Vec3::from(x + 1.0, y + 1.0, 0.0),
+        Vec3::from(x, y + 1.0, 0.0),
+    ];
+}
+
+#[cfg(test)]
+fn l_shape() -> NavMesh {
+    // 0 and 1 side by side, 2 on top of 1, given clockwise
+    let mut top = square(1.0, 1.0);
+    top.reverse();
+
+    return NavMesh::from(vec![square(0.0, 0.0), square(1.0, 0.0), top]);
+}
+
+#[test]
+fn navmesh_should_connect_polygons_with_shared_sides() {
+    let mesh = l_shape();
+
+    assert_eq!(4, mesh.portals.len());
+    assert!(mesh.portals.contains(&Portal {
+        from: 0,
+        to: 1,
+        left: Vec3::from(1.0, 1.0, 0.0),
+        right: Vec3::from(1.0, 0.0, 0.0),
+    }));
+    assert!(mesh.portals.iter().all(|portal| !(portal.from == 0 && portal.to == 2)));
+}
+
+#[test]
+fn navmesh_graph_should_have_centroid_positions() {
+    let graph = l_shape().graph();
+
+    assert_eq!(3, graph.node_count);
+    assert_eq!(4, graph.edges.len());
+    assert_eq!(&Vec3::from(1.5, 1.5, 0.0), graph.get_position(&2));
+    assert_eq!(1.0, graph.edges[0].weight);
+}
+
+#[test]
+fn navmesh_should_locate_points() {
+    let mesh = l_shape();
+
+    assert_eq!(Some(0), mesh.locate(&Vec3::from(0.5, 0.5, 0.0)));
+    assert_eq!(Some(2), mesh.locate(&Vec3::from(1.2, 1.8, 0.0)));
+    assert_eq!(None, mesh.locate(&Vec3::from(0.5, 1.5, 0.0)));
+}
+
+#[test]
+fn navmesh_path_should_bend_around_corner() {
+    let mesh = l_shape();
+
+    let path = mesh.find_path(&Vec3::from(0.5, 0.5, 0.0), &Vec3::from(1.2, 1.8, 0.0)).unwrap();
+    let back = mesh.find_path(&Vec3::from(1.2, 1.8, 0.0), &Vec3::from(0.5, 0.5, 0.0)).unwrap();
+
+    assert_eq!(vec![Vec3::from(0.5, 0.5, 0.0), Vec3::from(1.0, 1.0, 0.0), Vec3::from(1.2, 1.8, 0.0)], path);
+    assert_eq!(vec![Vec3::from(1.2, 1.8, 0.0), Vec3::from(1.0, 1.0, 0.0), Vec3::from(0.5, 0.5, 0.0)], back);
+}
+
+#[test]
+fn navmesh_path_should_be_straight_if_visible() {
+    let mesh = l_shape();
+
+    let straight = mesh.find_path(&Vec3::from(0.2, 0.5, 0.0), &Vec3::from(1.8, 0.9, 0.0)).unwrap();
+    let inside = mesh.find_path(&Vec3::from(0.2, 0.5, 0.0), &Vec3::from(0.8, 0.9, 0.0)).unwrap();
+    let upwards = mesh.find_path(&Vec3::from(1.5, 0.5, 0.0), &Vec3::from(1.5, 1.5, 0.0)).unwrap();
+
+    assert_eq!(2, straight.len());
+    assert_eq!(2, inside.len());
+    assert_eq!(2, upwards.len());
+    assert!(mesh.find_path(&Vec3::from(0.5, 0.5, 0.0), &Vec3::from(5.0, 5.0, 0.0)).is_none());
+}
+
+#[test]
+fn navmesh_should_connect_polygons_at_t_junctions() {
+    // 0 is a wide floor, 1 and 2 sit side by side along its top side
+    let wide = vec![Vec3::from(0.0, 0.0, 0.0), Vec3::from(2.0, 0.0, 0.0), Vec3::from(2.0, 1.0, 0.0), Vec3::from(0.0, 1.0, 0.0)];
+    let mesh = NavMesh::from(vec![wide, square(0.0, 1.0), square(1.0, 1.0)]);
+
+    assert!(mesh.portals.contains(&Portal {
+        from: 0,
+        to: 2,
+        left: Vec3::from(1.0, 1.0, 0.0),
+        right: Vec3::from(2.0, 1.0, 0.0),
+    }));
+    assert!(mesh.portals.iter().any(|portal| portal.from == 1 && portal.to == 0));
+    assert_eq!(6, mesh.portals.len());
+    assert_eq!(2, mesh.find_path(&Vec3::from(1.5, 0.5, 0.0), &Vec3::from(0.5, 1.5, 0.0)).unwrap().len());
+}
+
+#[test]
+fn navmesh_should_locate_points_on_stacked_floors() {
+    let upper: Vec<Vec3> = square(0.0, 0.0).into_iter().map(|vertex| Vec3::from(vertex.x, vertex.y, 3.0)).collect();
+    let ramp = vec![Vec3::from(1.0, 0.0, 0.0), Vec3::from(2.0, 0.0, 3.0), Vec3::from(2.0, 1.0, 3.0), Vec3::from(1.0, 1.0, 0.0)];
+    let mesh = NavMesh::from(vec![square(0.0, 0.0), upper, ramp]);
+
+    assert_eq!(Some(0), mesh.locate(&Vec3::from(0.5, 0.5, 0.2)));
+    assert_eq!(Some(1), mesh.locate(&Vec3::from(0.5, 0.5, 2.8)));
+    assert_eq!(Some(2), mesh.locate(&Vec3::from(1.5, 0.5, 1.5)));
+    assert_eq!(Some(vec![1]), mesh.corridor(1, 1));
+    assert_eq!(None, mesh.corridor(1, 2));
+}
+
+#[test]
+fn funnel_should_reject_corridor_without_portals() {
+    let mesh = l_shape();
+
+    assert_eq!(None, mesh.funnel(&Vec3::from(0.5, 0.5, 0.0), &Vec3::from(1.2, 1.8, 0.0), &[0, 2]));
+}
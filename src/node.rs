@@ -2,9 +2,13 @@ use std::cmp::Eq;
 use std::cmp::PartialEq;
 use std::hash::{Hash, Hasher};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 use crate::graph::Edge;
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Node {
     pub id: usize,
     pub edges: Vec<Edge>,
@@ -19,7 +23,8 @@ impl Node {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Vec3 {
     pub x: f32,
     pub y: f32,
@@ -76,6 +81,22 @@ impl Vec3 {
     pub fn manhattan_dist(&self, o: &Vec3) -> f32 {
         return (o.x - self.x).abs() + (o.y - self.y).abs() + (o.z - self.z).abs();
     }
+
+    pub fn chebyshev_dist(&self, o: &Vec3) -> f32 {
+        return (o.x - self.x).abs().max((o.y - self.y).abs()).max((o.z - self.z).abs());
+    }
+
+    // Distance in the x-y plane with diagonal steps costing sqrt(2).
+    pub fn octile_dist(&self, o: &Vec3) -> f32 {
+        let (dx, dy) = ((o.x - self.x).abs(), (o.y - self.y).abs());
+        return dx.max(dy) + (std::f32::consts::SQRT_2 - 1.0) * dx.min(dy);
+    }
+
+    // Distance on a hex grid in axial coordinates, x being the row and y the column.
+    pub fn hex_dist(&self, o: &Vec3) -> f32 {
+        let (dx, dy) = (o.x - self.x, o.y - self.y);
+        return (dx.abs() + dy.abs() + (dx + dy).abs()) / 2.0;
+    }
 }
 
 #[test]
@@ -120,4 +141,30 @@ fn test_manhattan_distance() {
 
     let dist = position1.manhattan_dist(&position2);
     assert_eq!(3.0, dist);
-}
\ No newline at end of file
+}
+#[test]
+fn test_chebyshev_distance() {
+    let position1 = Vec3::from(0.0, 0.0, 0.0);
+    let position2 = Vec3::from(1.0, 3.0, 2.0);
+
+    let dist = position1.chebyshev_dist(&position2);
+    assert_eq!(3.0, dist);
+}
+
+#[test]
+fn test_octile_distance() {
+    let position1 = Vec3::from(0.0, 0.0, 0.0);
+    let position2 = Vec3::from(1.0, 3.0, 0.0);
+
+    let dist = position1.octile_dist(&position2);
+    assert_eq!(2.0 + std::f32::consts::SQRT_2, dist);
+}
+
+#[test]
+fn test_hex_distance() {
+    let position1 = Vec3::from(0.0, 0.0, 0.0);
+
+    assert_eq!(1.0, position1.hex_dist(&Vec3::from(-1.0, 1.0, 0.0)));
+    assert_eq!(2.0, position1.hex_dist(&Vec3::from(1.0, 1.0, 0.0)));
+    assert_eq!(3.0, position1.hex_dist(&Vec3::from(3.0, -2.0, 0.0)));
+}
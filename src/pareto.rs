@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use ordered_float::NotNan;
+use priority_queue::DoublePriorityQueue;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::graph::{Edge, Graph};
+use crate::query::QueryOptions;
+use crate::search::dijkstra;
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ParetoPath {
+    pub costs: Vec<f32>,
+    pub path: Graph,
+}
+
+struct Label {
+    node_id: usize,
+    costs: Vec<f32>,
+    edge: Option<Edge>,
+    previous: Option<usize>,
+    dead: bool,
+}
+
+// No cost is higher, equal costs count as dominated to drop duplicate paths.
+fn dominates(costs1: &[f32], costs2: &[f32]) -> bool {
+    return costs1.iter().zip(costs2).all(|(cost1, cost2)| cost1 <= cost2);
+}
+
+fn key(costs: &[f32]) -> Vec<NotNan<f32>> {
+    return costs.iter().map(|cost| NotNan::new(*cost).unwrap()).collect();
+}
+
+// Martins' label-setting algorithm. Labels are settled in lexicographic order of their cost vectors, labels dominated
+// by another label at the same node or by a path already found to the target are dropped. Returns the Pareto front
+// ordered lexicographically by cost.
+pub fn pareto_paths(graph: &Graph, source: usize, target: usize, costs: &dyn Fn(&Edge) -> Vec<f32>) -> Vec<ParetoPath> {
+    if !graph.nodes_lookup.contains_key(&source) || !graph.nodes_lookup.contains_key(&target) {
+        return Vec::new();
+    }
+
+    let criteria = graph.edges.first().map_or(0, |edge| costs(edge).len());
+    let mut labels = vec![Label { node_id: source, costs: vec![0.0; criteria], edge: None, previous: None, dead: false }];
+    let mut at_node: HashMap<usize, Vec<usize>> = HashMap::from([(source, vec![0])]);
+    let mut found: Vec<usize> = Vec::new();
+    let mut queue: DoublePriorityQueue<usize, Vec<NotNan<f32>>> = DoublePriorityQueue::new();
+    queue.push(0, key(&labels[0].costs));
+
+    while let Some((current, _)) = queue.pop_min() {
+        if labels[current].dead {
+            continue;
+        }
+
+        let node_id = labels[current].node_id;
+        if node_id == target {
+            found.push(current);
+            continue;
+        }
+
+        for edge in &graph.nodes_lookup[&node_id].edges {
+            let next_costs: Vec<f32> = labels[current].costs.iter().zip(costs(edge)).map(|(cost, step)| cost + step).collect();
+
+            let dominated = |label: &usize| !labels[*label].dead && dominates(&labels[*label].costs, &next_costs);
+            if found.iter().any(dominated) || at_node.get(&edge.destination).is_some_and(|known| known.iter().any(dominated)) {
+                continue;
+            }
+
+            for label in at_node.get(&edge.destination).unwrap_or(&Vec::new()) {
+                if dominates(&next_costs, &labels[*label].costs) {
+                    labels[*label].dead = true;
+                }
+            }
+
+            let index = labels.len();
+            queue.push(index, key(&next_costs));
+            at_node.entry(edge.destination).or_default().push(index);
+            labels.push(Label { node_id: edge.destination, costs: next_costs, edge: Some(edge.clone()), previous: Some(current), dead: false });
+        }
+    }
+
+    return found.into_iter().map(|label| ParetoPath {
+        costs: labels[label].costs.clone(),
+        path: Graph::from(walk_back(&labels, label)),
+    }).collect();
+}
+
+fn walk_back(labels: &[Label], last: usize) -> Vec<Edge> {
+    let mut edges = Vec::new();
+    let mut current = Some(last);
+
+    while let Some(label) = current {
+        if let Some(edge) = &labels[label].edge {
+            edges.push(edge.clone());
+        }
+        current = labels[label].previous;
+    }
+
+    edges.reverse();
+    return edges;
+}
+
+// Single search on the weighted sum of the costs. With positive weights, the path is part of the Pareto front.
+pub fn weighted_sum_path(graph: &Graph,
+                         source: usize,
+                         target: usize,
+                         costs: &dyn Fn(&Edge) -> Vec<f32>,
+                         weights: &[f32]) -> Option<ParetoPath> {
+    let scalarized = Graph::from(graph.edges.iter()
+        .map(|edge| Edge::from(edge.index(), edge.source, edge.destination,
+                               costs(edge).iter().zip(weights).map(|(cost, weight)| cost * weight).sum()))
+        .collect());
+
+    let source_node = scalarized.nodes_lookup.get(&source)?;
+    let target_node = scalarized.nodes_lookup.get(&target)?;
+    let found = dijkstra(source_node.clone(), target_node.clone(), &scalarized, &|_| 0.0, &QueryOptions::new());
+
+    if found.edges.is_empty() && source != target {
+        return None;
+    }
+
+    let edges: Vec<Edge> = found.edges.iter().map(|edge| graph.edges_lookup[&edge.index()].clone()).collect();
+    let mut total = vec![0.0; weights.len()];
+    for edge in &edges {
+        for (sum, cost) in total.iter_mut().zip(costs(edge)) {
+            *sum += cost;
+        }
+    }
+
+    return Some(ParetoPath { costs: total, path: Graph::from(edges) });
+}
+
+
+#[cfg(test)]
+fn graph() -> (Graph, HashMap<usize, Vec<f32>>) {
+    // (distance, danger) of four routes from 0 to 3, the route over node 4 is dominated
+    let graph = Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 0.0),
+        Edge::from(1, 1, 3, 0.0),
+        Edge::from(2, 0, 2, 0.0),
+        Edge::from(3, 2, 3, 0.0),
+        Edge::from(4, 0, 3, 0.0),
+        Edge::from(5, 0, 4, 0.0),
+        Edge::from(6, 4, 3, 0.0),
+    ]));
+    let costs = HashMap::from([
+        (0, vec![1.0, 2.0]),
+        (1, vec![0.0, 3.0]),
+        (2, vec![1.0, 1.0]),
+        (3, vec![2.0, 2.0]),
+        (4, vec![5.0, 1.0]),
+        (5, vec![2.0, 2.0]),
+        (6, vec![2.0, 2.0]),
+    ]);
+
+    return (graph, costs);
+}
+
+#[test]
+fn pareto_paths_should_return_front() {
+    let (graph, costs) = graph();
+
+    let front = pareto_paths(&graph, 0, 3, &|edge| costs[&edge.index()].clone());
+
+    assert_eq!(vec![vec![1.0, 5.0], vec![3.0, 3.0], vec![5.0, 1.0]],
+               front.iter().map(|path| path.costs.clone()).collect::<Vec<Vec<f32>>>());
+    assert_eq!(vec![2, 3], front[1].path.edges.iter().map(|edge| edge.index()).collect::<Vec<usize>>());
+}
+
+#[test]
+fn pareto_paths_should_drop_duplicate_costs() {
+    let graph = Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 1.0),
+        Edge::from(1, 0, 1, 1.0),
+    ]));
+
+    let front = pareto_paths(&graph, 0, 1, &|edge| vec![edge.weight, 2.0]);
+
+    assert_eq!(1, front.len());
+}
+
+#[test]
+fn pareto_paths_should_be_empty_if_unreachable() {
+    let (graph, costs) = graph();
+
+    assert!(pareto_paths(&graph, 3, 0, &|edge| costs[&edge.index()].clone()).is_empty());
+}
+
+#[test]
+fn weighted_sum_path_should_follow_weights() {
+    let (graph, costs) = graph();
+    let criteria = |edge: &Edge| costs[&edge.index()].clone();
+
+    let shortest = weighted_sum_path(&graph, 0, 3, &criteria, &[1.0, 0.1]).unwrap();
+    let safest = weighted_sum_path(&graph, 0, 3, &criteria, &[0.1, 1.0]).unwrap();
+
+    assert_eq!(vec![1.0, 5.0], shortest.costs);
+    assert_eq!(vec![5.0, 1.0], safest.costs);
+    assert_eq!(vec![4], safest.path.edges.iter().map(|edge| edge.index()).collect::<Vec<usize>>());
+    assert!(weighted_sum_path(&graph, 3, 0, &criteria, &[1.0, 1.0]).is_none());
+}
@@ -2,11 +2,16 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 use crate::{graph::{Edge, Graph}};
 use crate::grid::{Direction, Grid};
 use crate::node::Node;
 #[cfg(test)]
 use crate::node::Vec3;
+use crate::query;
+use crate::query::QueryOptions;
 #[cfg(test)]
 use crate::search::AStar;
 #[cfg(test)]
@@ -37,9 +42,40 @@ impl Waypoint {
     }
 }
 
+// Path of a search together with the node ids it expanded, in order. None, if the search does not report expansions.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Traced {
+    pub path: Graph,
+    pub expanded: Option<Vec<usize>>,
+}
+
 pub trait PathFinding {
     fn graph(&self, source: Node, target: Node, graph: &Graph) -> Graph;
     fn grid(&self, source: (usize, usize), target: (usize, usize), grid: &Grid, directions: &[Direction]) -> Graph;
+
+    fn graph_traced(&self, source: Node, target: Node, graph: &Graph) -> Traced {
+        return Traced { path: self.graph(source, target, graph), expanded: None };
+    }
+
+    fn grid_traced(&self, source: (usize, usize), target: (usize, usize), grid: &Grid, directions: &[Direction]) -> Traced {
+        return Traced { path: self.grid(source, target, grid, directions), expanded: None };
+    }
+
+    fn graph_with(&self, source: Node, target: Node, graph: &Graph, options: &QueryOptions) -> Graph {
+        if options.resource.is_some() {
+            return query::resource_constrained(source.id, target.id, graph, options);
+        }
+
+        let pruned = options.prune(graph);
+        let source_node = pruned.nodes_lookup.get(&source.id);
+        let target_node = pruned.nodes_lookup.get(&target.id);
+
+        if source_node.is_none() || target_node.is_none() {
+            return Graph::from(Vec::new());
+        };
+
+        return self.graph(source_node.unwrap().clone(), target_node.unwrap().clone(), &pruned);
+    }
 }
 
 pub fn in_graph(source: usize, target: usize, graph: &Graph, path_finding: Box<dyn PathFinding>) -> Graph {
@@ -53,6 +89,18 @@ pub fn in_graph(source: usize, target: usize, graph: &Graph, path_finding: Box<d
     return path_finding.graph(source_node.unwrap().clone(), target_node.unwrap().clone(), graph);
 }
 
+pub fn in_graph_with(source: usize, target: usize, graph: &Graph, path_finding: Box<dyn PathFinding>,
+                     options: &QueryOptions) -> Graph {
+    let source_node = graph.nodes_lookup.get(&source);
+    let target_node = graph.nodes_lookup.get(&target);
+
+    if source_node.is_none() || target_node.is_none() {
+        return Graph::from(Vec::new());
+    };
+
+    return path_finding.graph_with(source_node.unwrap().clone(), target_node.unwrap().clone(), graph, options);
+}
+
 pub fn in_grid(source: (usize, usize), target: (usize, usize),
                grid: &Grid, path_finding: Box<dyn PathFinding>, directions: &[Direction]) -> Graph {
     if grid.outside(source) || grid.outside(target) {
@@ -228,7 +276,7 @@ fn dijkstra_in_grid_should_be_successful() {
     ]);
 
 
-    assert_eq!(47, dijkstra.edges.len())
+    assert_eq!(37, dijkstra.edges.len())
 }
 
 #[test]
@@ -246,7 +294,63 @@ fn dijkstra_in_grid_with_short_cut_should_be_successful() {
         Direction::DownRight,
     ]);
 
-    assert_eq!(16, dijkstra.edges.len())
+    assert_eq!(9, dijkstra.edges.len())
+}
+
+#[test]
+fn traced_searches_should_report_expanded_nodes() {
+    let grid = test_grid();
+    let directions = [Direction::Down, Direction::Right, Direction::Up, Direction::Left];
+    let searches: Vec<Box<dyn PathFinding>> = vec![
+        Box::from(Dijkstra {}),
+        Box::from(BreadthFirstSearch {}),
+        Box::from(DepthFirstSearch {}),
+        Box::from(BiBreadthFirstSearch {}),
+        Box::from(AStar { heuristic: Box::from(crate::search::manhattan_distance) }),
+    ];
+
+    for search in searches {
+        let traced = search.grid_traced((0, 0), (4, 4), &grid, &directions);
+        let expanded = traced.expanded.unwrap();
+
+        assert_eq!(search.grid((0, 0), (4, 4), &grid, &directions).edges.len(), traced.path.edges.len());
+        assert_eq!(Some(&0), expanded.first());
+    }
+
+    let hierarchical = crate::search::hierarchical_a_star::HierarchicalAStar { heuristic: Box::from(|_, _, _: &Graph| 0) };
+    assert!(hierarchical.grid_traced((0, 0), (4, 4), &grid, &directions).expanded.is_none());
+}
+
+#[test]
+fn in_graph_with_should_prune_graph_for_search_without_query_support() {
+    let graph = Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 1.0),
+        Edge::from(1, 1, 2, 1.0),
+        Edge::from(2, 0, 3, 1.0),
+        Edge::from(3, 3, 4, 1.0),
+        Edge::from(4, 4, 2, 1.0),
+    ]));
+    let options = QueryOptions {
+        blocked_nodes: HashSet::from([1]),
+        ..QueryOptions::new()
+    };
+
+    let bfs = in_graph_with(0, 2, &graph, Box::from(BreadthFirstSearch {}), &options);
+
+    assert_eq!(3, bfs.edges.len());
+    assert!(!bfs.nodes_lookup.contains_key(&1));
+}
+
+#[test]
+fn in_graph_with_blocked_target_should_return_empty_graph() {
+    let graph = Graph::from(Vec::from([Edge::from(0, 0, 1, 1.0)]));
+    let options = QueryOptions {
+        blocked_nodes: HashSet::from([1]),
+        ..QueryOptions::new()
+    };
+
+    assert!(in_graph_with(0, 1, &graph, Box::from(Dijkstra {}), &options).edges.is_empty());
+    assert!(in_graph_with(0, 1, &graph, Box::from(DepthFirstSearch {}), &options).edges.is_empty());
 }
 
 #[test]
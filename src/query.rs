@@ -0,0 +1,213 @@
+use std::collections::{HashMap, HashSet};
+
+use ordered_float::NotNan;
+use priority_queue::DoublePriorityQueue;
+
+use crate::graph::{Edge, Graph};
+
+pub struct Resource {
+    pub consumption: Box<dyn Fn(&Edge) -> f32>,
+    pub budget: f32,
+}
+
+// Restrictions applied to a single graph query. If allowed nodes are given, the path may only visit those,
+// including source and target.
+#[derive(Default)]
+pub struct QueryOptions {
+    pub blocked_nodes: HashSet<usize>,
+    pub blocked_edges: HashSet<usize>,
+    pub allowed_nodes: Option<HashSet<usize>>,
+    pub resource: Option<Resource>,
+}
+
+impl QueryOptions {
+    pub fn new() -> QueryOptions {
+        return QueryOptions::default();
+    }
+
+    pub fn permits_node(&self, node_id: usize) -> bool {
+        return !self.blocked_nodes.contains(&node_id)
+            && self.allowed_nodes.as_ref().is_none_or(|allowed| allowed.contains(&node_id));
+    }
+
+    pub fn permits(&self, edge: &Edge) -> bool {
+        return !self.blocked_edges.contains(&edge.index())
+            && self.permits_node(edge.source)
+            && self.permits_node(edge.destination);
+    }
+
+    pub fn prune(&self, graph: &Graph) -> Graph {
+        let mut pruned = Graph::from(graph.edges.iter()
+            .filter(|edge| self.permits(edge))
+            .cloned()
+            .collect());
+
+        if let Some(positions) = &graph.node_position_lookup {
+            pruned.offer_positions(positions.clone());
+        }
+
+        return pruned;
+    }
+}
+
+struct Label {
+    node_id: usize,
+    cost: f32,
+    resource: f32,
+    edge: Option<Edge>,
+    previous: Option<usize>,
+}
+
+// Label-setting search: keeps every path to a node, which is not dominated in both cost and resource consumption
+// by another path to that node, and settles labels by ascending cost.
+pub(crate) fn resource_constrained(source: usize, target: usize, graph: &Graph, options: &QueryOptions) -> Graph {
+    let resource = match &options.resource {
+        None => panic!("Resource constrained search requires a resource"),
+        Some(resource) => resource
+    };
+
+    if !options.permits_node(source) || !options.permits_node(target) {
+        return Graph::from(Vec::new());
+    }
+
+    let mut labels = vec![Label { node_id: source, cost: 0.0, resource: 0.0, edge: None, previous: None }];
+    let mut settled: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut queue: DoublePriorityQueue<usize, NotNan<f32>> = DoublePriorityQueue::new();
+    queue.push(0, NotNan::new(0.0).unwrap());
+
+    while let Some((current, _)) = queue.pop_min() {
+        let node_id = labels[current].node_id;
+
+        if dominated(&labels, settled.get(&node_id), labels[current].cost, labels[current].resource) {
+            continue;
+        }
+        settled.entry(node_id).or_default().push(current);
+
+        if node_id == target {
+            return Graph::from(walk_back(&labels, current));
+        }
+
+        let edges = graph.nodes_lookup.get(&node_id).map_or(&[][..], |node| &node.edges[..]);
+        for edge in edges {
+            if !options.permits(edge) {
+                continue;
+            }
+
+            let consumed = labels[current].resource + (resource.consumption)(edge);
+            let cost = labels[current].cost + edge.weight;
+
+            if consumed > resource.budget || dominated(&labels, settled.get(&edge.destination), cost, consumed) {
+                continue;
+            }
+
+            queue.push(labels.len(), NotNan::new(cost).unwrap());
+            labels.push(Label {
+                node_id: edge.destination,
+                cost,
+                resource: consumed,
+                edge: Some(edge.clone()),
+                previous: Some(current),
+            });
+        }
+    }
+
+    return Graph::from(Vec::new());
+}
+
+fn dominated(labels: &[Label], settled: Option<&Vec<usize>>, cost: f32, resource: f32) -> bool {
+    return settled.is_some_and(|settled| settled.iter()
+        .any(|label| labels[*label].cost <= cost && labels[*label].resource <= resource));
+}
+
+fn walk_back(labels: &[Label], last: usize) -> Vec<Edge> {
+    let mut edges = Vec::new();
+    let mut current = Some(last);
+
+    while let Some(label) = current {
+        if let Some(edge) = &labels[label].edge {
+            edges.push(edge.clone());
+        }
+        current = labels[label].previous;
+    }
+
+    edges.reverse();
+    return edges;
+}
+
+
+#[cfg(test)]
+fn graph() -> Graph {
+    return Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 1.0),
+        Edge::from(1, 1, 3, 1.0),
+        Edge::from(2, 0, 2, 2.0),
+        Edge::from(3, 2, 3, 2.0),
+        Edge::from(4, 0, 3, 10.0),
+    ]));
+}
+
+#[test]
+fn permits_should_respect_blocked_edges_and_nodes() {
+    let options = QueryOptions {
+        blocked_nodes: HashSet::from([2]),
+        blocked_edges: HashSet::from([0]),
+        ..QueryOptions::new()
+    };
+    let graph = graph();
+
+    assert!(!options.permits(&graph.edges[0]));
+    assert!(options.permits(&graph.edges[1]));
+    assert!(!options.permits(&graph.edges[2]));
+    assert!(!options.permits(&graph.edges[3]));
+}
+
+#[test]
+fn permits_should_respect_allowed_nodes() {
+    let options = QueryOptions {
+        allowed_nodes: Some(HashSet::from([0, 1, 3])),
+        ..QueryOptions::new()
+    };
+    let graph = graph();
+
+    assert!(options.permits(&graph.edges[0]));
+    assert!(!options.permits(&graph.edges[2]));
+}
+
+#[test]
+fn prune_should_remove_edges_and_keep_positions() {
+    let mut graph = graph();
+    graph.offer_positions(HashMap::from([(0, crate::node::Vec3::zeroed())]));
+
+    let options = QueryOptions {
+        blocked_nodes: HashSet::from([1]),
+        ..QueryOptions::new()
+    };
+    let pruned = options.prune(&graph);
+
+    assert_eq!(3, pruned.edges.len());
+    assert!(!pruned.nodes_lookup.contains_key(&1));
+    assert!(pruned.position_is_set());
+}
+
+#[test]
+fn resource_constrained_should_respect_budget() {
+    // fuel consumption is high on the fast route 0 -> 1 -> 3
+    let fuel = [5.0, 5.0, 1.0, 1.0, 1.0];
+    let graph = graph();
+
+    let with_budget = |budget: f32| QueryOptions {
+        resource: Some(Resource { consumption: Box::new(move |edge: &Edge| fuel[edge.index()]), budget }),
+        ..QueryOptions::new()
+    };
+
+    let unlimited = resource_constrained(0, 3, &graph, &with_budget(100.0));
+    let limited = resource_constrained(0, 3, &graph, &with_budget(2.0));
+    let tight = resource_constrained(0, 3, &graph, &with_budget(1.0));
+    let impossible = resource_constrained(0, 3, &graph, &with_budget(0.5));
+
+    assert_eq!(2, unlimited.edges.len());
+    assert_eq!(1, unlimited.edges[0].destination);
+    assert_eq!(vec![2, 3], limited.edges.iter().map(|edge| edge.index()).collect::<Vec<usize>>());
+    assert_eq!(vec![4], tight.edges.iter().map(|edge| edge.index()).collect::<Vec<usize>>());
+    assert!(impossible.edges.is_empty());
+}
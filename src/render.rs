@@ -0,0 +1,220 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::graph::Graph;
+use crate::grid::Grid;
+
+#[cfg(test)]
+use crate::graph::Edge;
+#[cfg(test)]
+use crate::grid::Direction;
+#[cfg(test)]
+use crate::node::Vec3;
+#[cfg(test)]
+use crate::path;
+#[cfg(test)]
+use crate::path::PathFinding;
+#[cfg(test)]
+use crate::search::cost;
+#[cfg(test)]
+use crate::search::Dijkstra;
+
+// Graphviz digraph with weights as edge labels. Positions, if set, are pinned for neato and fdp.
+pub fn to_dot(graph: &Graph) -> String {
+    return to_dot_highlighting(graph, &Graph::from(Vec::new()));
+}
+
+// Edges of the path, matched by index, source and destination, are drawn red and bold. Indices alone may repeat,
+// e.g. for grid arcs or the reverse edges of undirected graphs.
+pub fn to_dot_highlighting(graph: &Graph, path: &Graph) -> String {
+    let highlighted: HashSet<(usize, usize, usize)> = path.edges.iter()
+        .map(|edge| (edge.index(), edge.source, edge.destination))
+        .collect();
+    let mut node_ids: Vec<&usize> = graph.nodes_lookup.keys().collect();
+    node_ids.sort();
+
+    let mut dot = String::from("digraph G {\n");
+    for node_id in node_ids {
+        match graph.node_position_lookup.as_ref().and_then(|positions| positions.get(node_id)) {
+            None => writeln!(dot, "  {node_id};").unwrap(),
+            Some(position) => writeln!(dot, "  {node_id} [pos=\"{},{}!\"];", position.x, position.y).unwrap()
+        }
+    }
+
+    for edge in &graph.edges {
+        let style = if highlighted.contains(&(edge.index(), edge.source, edge.destination)) { ", color=\"red\", penwidth=2" } else { "" };
+        writeln!(dot, "  {} -> {} [label=\"{}\"{style}];", edge.source, edge.destination, edge.weight).unwrap();
+    }
+
+    dot.push_str("}\n");
+    return dot;
+}
+
+// Draws cells shaded by cost, darker is more expensive, blocked cells black. Visited cells, e.g. the expanded nodes of
+// a traced search, are tinted blue and the path is drawn as a red line through the cell centers.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GridSvg {
+    pub cell_size: f32,
+    pub show_costs: bool,
+}
+
+impl GridSvg {
+    pub fn new() -> GridSvg {
+        return GridSvg { cell_size: 24.0, show_costs: true };
+    }
+
+    pub fn render(&self, grid: &Grid, path: &Graph, visited: &[usize]) -> String {
+        let size = self.cell_size;
+        let (width, height) = (grid.width as f32 * size, grid.height as f32 * size);
+        let walkable: Vec<f32> = (0..grid.size).filter(|node_id| grid.walkable(*node_id)).map(|node_id| grid.cost(node_id)).collect();
+        let min_cost = walkable.iter().cloned().fold(f32::INFINITY, f32::min);
+        let max_cost = walkable.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+
+        let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+            viewBox=\"0 0 {width} {height}\">\n");
+
+        for node_id in 0..grid.size {
+            let (x, y) = self.corner(grid, node_id);
+            let fill = match grid.walkable(node_id) {
+                false => String::from("#222222"),
+                true => {
+                    let share = if max_cost > min_cost { (grid.cost(node_id) - min_cost) / (max_cost - min_cost) } else { 0.0 };
+                    let shade = (255.0 - 155.0 * share).round() as u8;
+                    format!("#{shade:02x}{shade:02x}{shade:02x}")
+                }
+            };
+            writeln!(svg, "  <rect x=\"{x}\" y=\"{y}\" width=\"{size}\" height=\"{size}\" fill=\"{fill}\" stroke=\"#cccccc\"/>").unwrap();
+        }
+
+        // in order of the first visit, so the same search always renders the same document
+        let mut drawn: HashSet<usize> = HashSet::new();
+        for node_id in visited.iter().filter(|node_id| drawn.insert(**node_id)) {
+            let (x, y) = self.corner(grid, *node_id);
+            writeln!(svg, "  <rect class=\"visited\" x=\"{x}\" y=\"{y}\" width=\"{size}\" height=\"{size}\" \
+                fill=\"#3a86ff\" fill-opacity=\"0.35\"/>").unwrap();
+        }
+
+        if self.show_costs {
+            for node_id in (0..grid.size).filter(|node_id| grid.walkable(*node_id)) {
+                let (x, y) = self.center(grid, node_id);
+                writeln!(svg, "  <text x=\"{x}\" y=\"{y}\" font-size=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+                         size / 3.0, grid.cost(node_id)).unwrap();
+            }
+        }
+
+        if let Some(first) = path.edges.first() {
+            let mut nodes = vec![first.source];
+            nodes.extend(path.edges.iter().map(|edge| edge.destination));
+            let points: Vec<String> = nodes.iter().map(|node_id| {
+                let (x, y) = self.center(grid, *node_id);
+                return format!("{x},{y}");
+            }).collect();
+
+            writeln!(svg, "  <polyline points=\"{}\" fill=\"none\" stroke=\"#e63946\" stroke-width=\"{}\" \
+                stroke-linecap=\"round\" stroke-linejoin=\"round\"/>", points.join(" "), size / 5.0).unwrap();
+        }
+
+        svg.push_str("</svg>\n");
+        return svg;
+    }
+
+    fn corner(&self, grid: &Grid, node_id: usize) -> (f32, f32) {
+        let (row, col) = grid.coords(node_id);
+        return (col as f32 * self.cell_size, row as f32 * self.cell_size);
+    }
+
+    fn center(&self, grid: &Grid, node_id: usize) -> (f32, f32) {
+        let (x, y) = self.corner(grid, node_id);
+        return (x + self.cell_size / 2.0, y + self.cell_size / 2.0);
+    }
+}
+
+impl Default for GridSvg {
+    fn default() -> GridSvg {
+        return GridSvg::new();
+    }
+}
+
+
+#[cfg(test)]
+fn graph() -> Graph {
+    return Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 1.0),
+        Edge::from(1, 1, 2, 2.5),
+        Edge::from(2, 0, 2, 5.0),
+    ]));
+}
+
+#[test]
+fn to_dot_should_list_nodes_and_edges() {
+    let mut graph = graph();
+    graph.offer_positions([(0, Vec3::from(0.0, 0.0, 0.0)), (1, Vec3::from(1.0, 2.0, 0.0)), (2, Vec3::from(2.0, 0.0, 0.0))].into());
+
+    let dot = to_dot(&graph);
+
+    assert!(dot.starts_with("digraph G {\n  0 [pos=\"0,0!\"];\n  1 [pos=\"1,2!\"];"));
+    assert!(dot.contains("  1 -> 2 [label=\"2.5\"];\n"));
+    assert!(!dot.contains("color"));
+}
+
+#[test]
+fn to_dot_should_highlight_path_edges() {
+    let graph = graph();
+    let path = path::in_graph(0, 2, &graph, Box::from(Dijkstra {}));
+
+    let dot = to_dot_highlighting(&graph, &path);
+
+    assert_eq!(2, dot.matches("color=\"red\"").count());
+    assert!(dot.contains("  0 -> 2 [label=\"5\"];\n"));
+}
+
+#[test]
+fn grid_svg_should_draw_visited_cells_in_order() {
+    let grid = Grid::from(&[&[1.0, 1.0, 1.0, 1.0]]);
+    let svg = GridSvg::new().render(&grid, &Graph::from(Vec::new()), &[3, 1, 3, 0, 1]);
+
+    let visited: Vec<&str> = svg.lines().filter(|line| line.contains("class=\"visited\"")).collect();
+
+    assert_eq!(3, visited.len());
+    assert!(visited[0].contains("x=\"72\"") && visited[1].contains("x=\"24\"") && visited[2].contains("x=\"0\""));
+}
+
+#[test]
+fn to_dot_should_not_highlight_edges_sharing_an_index() {
+    let graph = Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 1.0),
+        Edge::from(0, 1, 0, 1.0),
+        Edge::from(1, 1, 2, 2.0),
+    ]));
+    let path = Graph::from(Vec::from([Edge::from(0, 0, 1, 1.0)]));
+
+    let dot = to_dot_highlighting(&graph, &path);
+
+    assert_eq!(1, dot.matches("color=\"red\"").count());
+    assert!(dot.contains("  0 -> 1 [label=\"1\", color=\"red\", penwidth=2];\n"));
+    assert!(dot.contains("  1 -> 0 [label=\"1\"];\n"));
+}
+
+#[test]
+fn grid_svg_should_draw_cells_visited_nodes_and_path() {
+    let grid = Grid::from(&[
+        &[1.0, 3.0, 1.0],
+        &[1.0, cost::INFINITY, 1.0],
+    ]);
+    let directions = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+
+    let traced = Dijkstra {}.grid_traced((1, 0), (1, 2), &grid, &directions);
+    let visited = traced.expanded.unwrap();
+    let svg = GridSvg::new().render(&grid, &traced.path, &visited);
+
+    assert_eq!(5, visited.len());
+    assert_eq!(6, svg.matches("<rect x=").count());
+    assert_eq!(1, svg.matches("fill=\"#222222\"").count());
+    assert_eq!(1, svg.matches("fill=\"#646464\"").count());
+    assert_eq!(5, svg.matches("class=\"visited\"").count());
+    assert!(svg.contains("points=\"12,36 12,12 36,12 60,12 60,36\""));
+    assert!(svg.contains(">3</text>"));
+}
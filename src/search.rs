@@ -1,14 +1,22 @@
 pub use a_star::*;
+pub use alt::*;
 pub use breadth_first::*;
+pub use d_star_lite::*;
 pub use depth_first::*;
 pub use dijkstra::*;
+pub use heuristic::*;
 
 pub mod a_star;
+pub mod alt;
 pub mod breadth_first;
 pub mod breadth_first_bi;
+pub mod d_star_lite;
 pub mod depth_first;
 pub mod dijkstra;
+pub mod heuristic;
 pub mod hierarchical_a_star;
+pub(crate) mod adjacency;
 pub(crate) mod cost;
+pub(crate) mod expansions;
 mod probing;
 mod probing_bi;
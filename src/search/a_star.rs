@@ -2,11 +2,15 @@
 use std::collections::HashMap;
 
 use crate::{graph::Graph, path::PathFinding};
+use crate::path::Traced;
 #[cfg(test)]
 use crate::graph::Edge;
 use crate::grid::{Direction, Grid};
 use crate::node::{Node, Vec3};
-use crate::search::{dijkstra, dijkstra_grid};
+use crate::query;
+use crate::query::QueryOptions;
+use crate::search::{dijkstra_grid_traced, dijkstra_traced};
+use crate::search::expansions::Expansions;
 
 pub fn euclidean_distance(src: &Vec3, dest: &Vec3) -> f32 {
     return src.euclidean_dist(dest);
@@ -16,18 +20,74 @@ pub fn manhattan_distance(src: &Vec3, dest: &Vec3) -> f32 {
     return src.manhattan_dist(dest);
 }
 
+pub fn chebyshev_distance(src: &Vec3, dest: &Vec3) -> f32 {
+    return src.chebyshev_dist(dest);
+}
+
+pub fn octile_distance(src: &Vec3, dest: &Vec3) -> f32 {
+    return src.octile_dist(dest);
+}
+
+pub fn hex_distance(src: &Vec3, dest: &Vec3) -> f32 {
+    return src.hex_dist(dest);
+}
+
 pub struct AStar {
     pub heuristic: Box<dyn Fn(&Vec3, &Vec3) -> f32>,
 }
 
+impl AStar {
+    fn search_graph(&self, source: Node, target: Node, graph: &Graph, options: &QueryOptions, expansions: &mut Expansions) -> Graph {
+        graph.verify_positions();
+        let target_position = graph.get_position(&target.id);
+        let heuristic = |node_id: usize| (self.heuristic)(graph.get_position(&node_id), target_position);
+
+        return dijkstra_traced(source, target, graph, &heuristic, options, expansions);
+    }
+
+    fn search_grid(&self,
+                   source: (usize, usize),
+                   target: (usize, usize),
+                   grid: &Grid,
+                   directions: &[Direction],
+                   expansions: &mut Expansions) -> Graph {
+        let target_position = Vec3::from(target.0 as f32, target.1 as f32, 0.0);
+        let heuristic = |node_id: usize| {
+            let (row, col) = grid.coords(node_id);
+            return (self.heuristic)(&Vec3::from(row as f32, col as f32, 0.0), &target_position);
+        };
+
+        return dijkstra_grid_traced(source, target, grid, directions, &heuristic, expansions);
+    }
+}
+
 impl PathFinding for AStar {
     fn graph(&self, source: Node, target: Node, graph: &Graph) -> Graph {
-        graph.verify_positions();
-        return dijkstra(source, target, graph, &self.heuristic);
+        return self.graph_with(source, target, graph, &QueryOptions::new());
+    }
+
+    fn graph_with(&self, source: Node, target: Node, graph: &Graph, options: &QueryOptions) -> Graph {
+        if options.resource.is_some() {
+            return query::resource_constrained(source.id, target.id, graph, options);
+        }
+
+        return self.search_graph(source, target, graph, options, &mut Expansions::off());
     }
 
     fn grid(&self, source: (usize, usize), target: (usize, usize), grid: &Grid, directions: &[Direction]) -> Graph {
-        return dijkstra_grid(source, target, grid, directions, &self.heuristic);
+        return self.search_grid(source, target, grid, directions, &mut Expansions::off());
+    }
+
+    fn graph_traced(&self, source: Node, target: Node, graph: &Graph) -> Traced {
+        let mut expansions = Expansions::on();
+        let path = self.search_graph(source, target, graph, &QueryOptions::new(), &mut expansions);
+        return Traced { path, expanded: expansions.into_nodes() };
+    }
+
+    fn grid_traced(&self, source: (usize, usize), target: (usize, usize), grid: &Grid, directions: &[Direction]) -> Traced {
+        let mut expansions = Expansions::on();
+        let path = self.search_grid(source, target, grid, directions, &mut expansions);
+        return Traced { path, expanded: expansions.into_nodes() };
     }
 }
 
@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+
+use ordered_float::NotNan;
+use priority_queue::DoublePriorityQueue;
+
+use crate::graph::Graph;
+
+// Outgoing or incoming neighbours with the edge weight, for searches which run many times over the same graph.
+pub(crate) type Adjacency = HashMap<usize, Vec<(usize, f32)>>;
+
+pub(crate) fn adjacency(graph: &Graph) -> (Adjacency, Adjacency) {
+    let mut forward: Adjacency = HashMap::new();
+    let mut backward: Adjacency = HashMap::new();
+
+    for edge in &graph.edges {
+        forward.entry(edge.source).or_default().push((edge.destination, edge.weight));
+        backward.entry(edge.destination).or_default().push((edge.source, edge.weight));
+    }
+
+    return (forward, backward);
+}
+
+// Distances from the source to every reachable node.
+pub(crate) fn distances(source: usize, adjacency: &Adjacency) -> HashMap<usize, f32> {
+    let mut distances: HashMap<usize, f32> = HashMap::new();
+    let mut queue: DoublePriorityQueue<usize, NotNan<f32>> = DoublePriorityQueue::new();
+    queue.push(source, NotNan::new(0.0).unwrap());
+
+    while let Some((node_id, distance)) = queue.pop_min() {
+        distances.insert(node_id, distance.into_inner());
+
+        for (neighbour, weight) in adjacency.get(&node_id).unwrap_or(&Vec::new()) {
+            if distances.contains_key(neighbour) {
+                continue;
+            }
+
+            let cost = distance + weight;
+            if queue.get_priority(neighbour).is_some_and(|queued| *queued <= cost) {
+                continue;
+            }
+
+            queue.push(*neighbour, cost);
+        }
+    }
+
+    return distances;
+}
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::graph::Graph;
+#[cfg(test)]
+use crate::graph::Edge;
+use crate::grid::{CostModel, Direction, Grid};
+use crate::node::Node;
+use crate::path::{PathFinding, Traced};
+use crate::query;
+use crate::query::QueryOptions;
+use crate::search::{dijkstra_grid_traced, dijkstra_traced};
+use crate::search::adjacency::{adjacency, distances, Adjacency};
+use crate::search::expansions::Expansions;
+
+// Precomputed distances from and to a few landmark nodes. By the triangle inequality, the differences of these
+// distances are lower bounds for the distance between any two nodes, so no node positions are needed.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Landmarks {
+    landmarks: Vec<usize>,
+    from_landmark: Vec<HashMap<usize, f32>>,
+    to_landmark: Vec<HashMap<usize, f32>>,
+}
+
+impl Landmarks {
+    // Selects landmarks greedily, each one being the node farthest away from the already selected landmarks.
+    pub fn from(graph: &Graph, count: usize) -> Landmarks {
+        let (forward, backward) = adjacency(graph);
+        return Landmarks::select(&forward, &backward, graph.nodes_lookup.keys().cloned().collect(), count);
+    }
+
+    pub fn from_nodes(graph: &Graph, nodes: &[usize]) -> Landmarks {
+        let (forward, backward) = adjacency(graph);
+        return Landmarks::compute(&forward, &backward, nodes.to_vec());
+    }
+
+    // Landmarks for searches on a grid, the node ids are the ids of the grid cells.
+    pub fn from_grid(grid: &Grid, directions: &[Direction], count: usize) -> Landmarks {
+        let (forward, backward) = grid_adjacency(grid, directions);
+        return Landmarks::select(&forward, &backward, (0..grid.size).collect(), count);
+    }
+
+    pub fn landmarks(&self) -> &[usize] {
+        return &self.landmarks;
+    }
+
+    // Lower bound for the distance from the node to the target.
+    pub fn estimate(&self, node_id: usize, target: usize) -> f32 {
+        let mut estimate: f32 = 0.0;
+
+        for landmark in 0..self.landmarks.len() {
+            let from = &self.from_landmark[landmark];
+            if let (Some(to_node), Some(to_target)) = (from.get(&node_id), from.get(&target)) {
+                estimate = estimate.max(to_target - to_node);
+            }
+
+            let to = &self.to_landmark[landmark];
+            if let (Some(from_node), Some(from_target)) = (to.get(&node_id), to.get(&target)) {
+                estimate = estimate.max(from_node - from_target);
+            }
+        }
+
+        return estimate;
+    }
+
+    fn select(forward: &Adjacency, backward: &Adjacency, mut nodes: Vec<usize>, count: usize) -> Landmarks {
+        nodes.sort();
+        let mut landmarks = Vec::new();
+        let mut closest: HashMap<usize, f32> = nodes.iter().map(|node_id| (*node_id, f32::INFINITY)).collect();
+
+        // The first landmark is the node farthest away from the node with the smallest id.
+        if let Some(first) = nodes.first() {
+            let distances = distances(*first, forward);
+            let farthest = nodes.iter()
+                .max_by(|node1, node2| distance(&distances, node1).total_cmp(&distance(&distances, node2)));
+            landmarks.push(*farthest.unwrap());
+        }
+
+        while landmarks.len() < count.min(nodes.len()) {
+            let distances = distances(*landmarks.last().unwrap(), forward);
+            for (node_id, closest) in closest.iter_mut() {
+                *closest = closest.min(distance(&distances, node_id));
+            }
+
+            let farthest = nodes.iter()
+                .filter(|node_id| !landmarks.contains(node_id))
+                .max_by(|node1, node2| closest[node1].total_cmp(&closest[node2]));
+            landmarks.push(*farthest.unwrap());
+        }
+
+        landmarks.truncate(count);
+        return Landmarks::compute(forward, backward, landmarks);
+    }
+
+    fn compute(forward: &Adjacency, backward: &Adjacency, landmarks: Vec<usize>) -> Landmarks {
+        return Landmarks {
+            from_landmark: landmarks.iter().map(|landmark| distances(*landmark, forward)).collect(),
+            to_landmark: landmarks.iter().map(|landmark| distances(*landmark, backward)).collect(),
+            landmarks,
+        };
+    }
+}
+
+// Blocked cells included, as the grid searches may enter them at their cost.
+fn grid_adjacency(grid: &Grid, directions: &[Direction]) -> (Adjacency, Adjacency) {
+    return adjacency(&Graph::from_grid_with_blocked(grid, directions, CostModel::Destination));
+}
+
+fn distance(distances: &HashMap<usize, f32>, node_id: &usize) -> f32 {
+    return *distances.get(node_id).unwrap_or(&f32::INFINITY);
+}
+
+// A* with the landmark (ALT) heuristic. Use landmarks computed for the graph or grid that is searched.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AltAStar {
+    pub landmarks: Landmarks,
+}
+
+impl AltAStar {
+    fn search_graph(&self, source: Node, target: Node, graph: &Graph, options: &QueryOptions, expansions: &mut Expansions) -> Graph {
+        let target_id = target.id;
+        return dijkstra_traced(source, target, graph, &|node_id| self.landmarks.estimate(node_id, target_id),
+                               options, expansions);
+    }
+
+    fn search_grid(&self,
+                   source: (usize, usize),
+                   target: (usize, usize),
+                   grid: &Grid,
+                   directions: &[Direction],
+                   expansions: &mut Expansions) -> Graph {
+        let target_id = grid.node_id(target);
+        return dijkstra_grid_traced(source, target, grid, directions,
+                                    &|node_id| self.landmarks.estimate(node_id, target_id), expansions);
+    }
+}
+
+impl PathFinding for AltAStar {
+    fn graph(&self, source: Node, target: Node, graph: &Graph) -> Graph {
+        return self.graph_with(source, target, graph, &QueryOptions::new());
+    }
+
+    fn graph_with(&self, source: Node, target: Node, graph: &Graph, options: &QueryOptions) -> Graph {
+        if options.resource.is_some() {
+            return query::resource_constrained(source.id, target.id, graph, options);
+        }
+
+        return self.search_graph(source, target, graph, options, &mut Expansions::off());
+    }
+
+    fn grid(&self, source: (usize, usize), target: (usize, usize), grid: &Grid, directions: &[Direction]) -> Graph {
+        return self.search_grid(source, target, grid, directions, &mut Expansions::off());
+    }
+
+    fn graph_traced(&self, source: Node, target: Node, graph: &Graph) -> Traced {
+        let mut expansions = Expansions::on();
+        let path = self.search_graph(source, target, graph, &QueryOptions::new(), &mut expansions);
+        return Traced { path, expanded: expansions.into_nodes() };
+    }
+
+    fn grid_traced(&self, source: (usize, usize), target: (usize, usize), grid: &Grid, directions: &[Direction]) -> Traced {
+        let mut expansions = Expansions::on();
+        let path = self.search_grid(source, target, grid, directions, &mut expansions);
+        return Traced { path, expanded: expansions.into_nodes() };
+    }
+}
+
+
+#[cfg(test)]
+fn graph() -> Graph {
+    return Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 4.0),
+        Edge::from(1, 0, 2, 2.0),
+        Edge::from(2, 1, 2, 3.0),
+        Edge::from(3, 1, 3, 2.0),
+        Edge::from(4, 1, 4, 3.0),
+        Edge::from(5, 2, 1, 1.0),
+        Edge::from(6, 2, 3, 4.0),
+        Edge::from(7, 2, 4, 5.0),
+        Edge::from(8, 4, 3, 1.0),
+        Edge::from(9, 3, 5, 2.0),
+        Edge::from(10, 5, 0, 6.0),
+    ]));
+}
+
+#[cfg(test)]
+fn cost(path: &Graph) -> f32 {
+    return path.edges.iter().map(|edge| edge.weight).sum();
+}
+
+#[test]
+fn landmarks_should_select_distinct_farthest_nodes() {
+    let landmarks = Landmarks::from(&graph(), 3);
+
+    // node 5 is farthest away from node 0
+    assert_eq!(3, landmarks.landmarks().len());
+    assert_eq!(5, landmarks.landmarks()[0]);
+    assert!(!landmarks.landmarks()[1..].contains(&5));
+    assert_ne!(landmarks.landmarks()[1], landmarks.landmarks()[2]);
+}
+
+#[test]
+fn landmark_estimate_should_be_admissible() {
+    let graph = graph();
+    let landmarks = Landmarks::from(&graph, 2);
+    let (forward, _) = adjacency(&graph);
+
+    for node_id in 0..6 {
+        let exact = distances(node_id, &forward);
+        for target in 0..6 {
+            assert!(landmarks.estimate(node_id, target) <= exact[&target]);
+        }
+        assert_eq!(0.0, landmarks.estimate(node_id, node_id));
+    }
+}
+
+#[test]
+fn landmark_estimate_should_be_exact_towards_landmark() {
+    let graph = graph();
+    let landmarks = Landmarks::from_nodes(&graph, &[3]);
+
+    assert_eq!(5.0, landmarks.estimate(0, 3));
+}
+
+#[test]
+fn alt_should_find_shortest_path_without_positions() {
+    let graph = graph();
+    let alt = AltAStar { landmarks: Landmarks::from(&graph, 2) };
+    let dijkstra = crate::search::Dijkstra {};
+
+    for source in 0..6 {
+        for target in 0..6 {
+            let source = graph.nodes_lookup[&source].clone();
+            let target = graph.nodes_lookup[&target].clone();
+
+            let expected = dijkstra.graph(source.clone(), target.clone(), &graph);
+            let path = alt.graph(source, target, &graph);
+            assert_eq!(cost(&expected), cost(&path));
+        }
+    }
+}
+
+#[test]
+fn alt_should_respect_query_options() {
+    let graph = graph();
+    let alt = AltAStar { landmarks: Landmarks::from(&graph, 2) };
+    let options = QueryOptions {
+        blocked_edges: std::collections::HashSet::from([3]),
+        ..QueryOptions::new()
+    };
+
+    let path = alt.graph_with(graph.nodes_lookup[&0].clone(), graph.nodes_lookup[&3].clone(), &graph, &options);
+
+    assert_eq!(6.0, cost(&path));
+}
+
+#[test]
+fn alt_should_find_shortest_path_in_grid() {
+    let grid = Grid::from(&[
+        &[1.0, 1.0, 1.0, 1.0],
+        &[1.0, 9.0, 9.0, 1.0],
+        &[1.0, 1.0, 9.0, 1.0],
+    ]);
+    let directions = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+    let alt = AltAStar { landmarks: Landmarks::from_grid(&grid, &directions, 2) };
+
+    let expected = crate::search::Dijkstra {}.grid((2, 0), (2, 3), &grid, &directions);
+    let path = alt.grid((2, 0), (2, 3), &grid, &directions);
+
+    assert_eq!(cost(&expected), cost(&path));
+    assert_eq!(7.0, cost(&path));
+}
+
+#[test]
+fn alt_should_expand_fewer_nodes_than_dijkstra() {
+    let graph = graph();
+    let alt = AltAStar { landmarks: Landmarks::from(&graph, 2) };
+    let (source, target) = (graph.nodes_lookup[&0].clone(), graph.nodes_lookup[&3].clone());
+
+    let traced = alt.graph_traced(source.clone(), target.clone(), &graph);
+    let dijkstra = crate::search::Dijkstra {}.graph_traced(source, target, &graph);
+
+    assert_eq!(cost(&dijkstra.path), cost(&traced.path));
+    assert!(traced.expanded.unwrap().len() <= dijkstra.expanded.unwrap().len());
+}
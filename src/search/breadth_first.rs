@@ -1,18 +1,31 @@
 use crate::graph::Graph;
 use crate::grid::{Direction, Grid};
 use crate::node::Node;
-use crate::path::PathFinding;
+use crate::path::{PathFinding, Traced};
 use crate::search::probing;
+use crate::search::expansions::Expansions;
 use crate::search::probing::{probe_graph, probe_grid};
 
 pub struct BreadthFirstSearch {}
 
 impl PathFinding for BreadthFirstSearch {
     fn graph(&self, source: Node, target: Node, graph: &Graph) -> Graph {
-        return probe_graph(source.id, target.id, graph, probing::dequeue);
+        return probe_graph(source.id, target.id, graph, probing::dequeue, &mut Expansions::off());
     }
 
     fn grid(&self, source: (usize, usize), target: (usize, usize), grid: &Grid, directions: &[Direction]) -> Graph {
-        return probe_grid(source, target, grid, directions, probing::dequeue);
+        return probe_grid(source, target, grid, directions, probing::dequeue, &mut Expansions::off());
+    }
+
+    fn graph_traced(&self, source: Node, target: Node, graph: &Graph) -> Traced {
+        let mut expansions = Expansions::on();
+        let path = probe_graph(source.id, target.id, graph, probing::dequeue, &mut expansions);
+        return Traced { path, expanded: expansions.into_nodes() };
+    }
+
+    fn grid_traced(&self, source: (usize, usize), target: (usize, usize), grid: &Grid, directions: &[Direction]) -> Traced {
+        let mut expansions = Expansions::on();
+        let path = probe_grid(source, target, grid, directions, probing::dequeue, &mut expansions);
+        return Traced { path, expanded: expansions.into_nodes() };
     }
 }
\ No newline at end of file
@@ -1,17 +1,30 @@
 use crate::graph::Graph;
 use crate::grid::{Direction, Grid};
 use crate::node::Node;
-use crate::path::PathFinding;
+use crate::path::{PathFinding, Traced};
+use crate::search::expansions::Expansions;
 use crate::search::probing_bi::{probe_graph, probe_grid};
 
 pub struct BiBreadthFirstSearch {}
 
 impl PathFinding for BiBreadthFirstSearch {
     fn graph(&self, source: Node, target: Node, graph: &Graph) -> Graph {
-        return probe_graph(source, target, graph);
+        return probe_graph(source, target, graph, &mut Expansions::off());
     }
 
     fn grid(&self, source: (usize, usize), target: (usize, usize), grid: &Grid, directions: &[Direction]) -> Graph {
-        return probe_grid(source, target, grid, directions);
+        return probe_grid(source, target, grid, directions, &mut Expansions::off());
+    }
+
+    fn graph_traced(&self, source: Node, target: Node, graph: &Graph) -> Traced {
+        let mut expansions = Expansions::on();
+        let path = probe_graph(source, target, graph, &mut expansions);
+        return Traced { path, expanded: expansions.into_nodes() };
+    }
+
+    fn grid_traced(&self, source: (usize, usize), target: (usize, usize), grid: &Grid, directions: &[Direction]) -> Traced {
+        let mut expansions = Expansions::on();
+        let path = probe_grid(source, target, grid, directions, &mut expansions);
+        return Traced { path, expanded: expansions.into_nodes() };
     }
 }
\ No newline at end of file
@@ -0,0 +1,356 @@
+use std::collections::HashMap;
+
+use ordered_float::NotNan;
+use priority_queue::DoublePriorityQueue;
+
+use crate::graph::{Edge, Graph};
+use crate::grid::{CellChange, CostModel, Direction, Grid};
+use crate::node::Vec3;
+#[cfg(test)]
+use crate::path::PathFinding;
+#[cfg(test)]
+use crate::search::Dijkstra;
+
+type Key = (NotNan<f32>, NotNan<f32>);
+type Heuristic = Box<dyn Fn(&Vec3, &Vec3) -> f32>;
+
+// Incremental planner for a single goal. It searches backwards from the goal, so the start may move and costs may
+// change between queries, and only the part of the search affected by the changes is repaired.
+pub struct DStarLite {
+    start: usize,
+    last_start: usize,
+    goal: usize,
+    arcs: Vec<Edge>,
+    successors: HashMap<usize, Vec<usize>>,
+    predecessors: HashMap<usize, Vec<usize>>,
+    edge_positions: HashMap<usize, usize>,
+    positions: Option<HashMap<usize, Vec3>>,
+    heuristic: Heuristic,
+    g: HashMap<usize, f32>,
+    rhs: HashMap<usize, f32>,
+    queue: DoublePriorityQueue<usize, Key>,
+    k_m: f32,
+    expanded: usize,
+}
+
+impl DStarLite {
+    // The heuristic is only used if the graph has node positions.
+    pub fn from_graph(start: usize, goal: usize, graph: &Graph, heuristic: Heuristic) -> DStarLite {
+        return DStarLite::from(start, goal, graph.edges.clone(), graph.node_position_lookup.clone(), heuristic);
+    }
+
+    // Cells are positioned at (row, column, 0), moving into a cell costs the cell's cost. Arcs are numbered in order of
+    // their source cell and the directions, blocked cells included, as they may be unblocked later.
+    pub fn from_grid(start: (usize, usize),
+                     goal: (usize, usize),
+                     grid: &Grid,
+                     directions: &[Direction],
+                     heuristic: Heuristic) -> DStarLite {
+        let graph = Graph::from_grid_with_blocked(grid, directions, CostModel::Destination);
+        return DStarLite::from(grid.node_id(start), grid.node_id(goal), graph.edges, graph.node_position_lookup, heuristic);
+    }
+
+    fn from(start: usize,
+            goal: usize,
+            arcs: Vec<Edge>,
+            positions: Option<HashMap<usize, Vec3>>,
+            heuristic: Heuristic) -> DStarLite {
+        let mut successors: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut predecessors: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut edge_positions = HashMap::new();
+
+        for (position, arc) in arcs.iter().enumerate() {
+            successors.entry(arc.source).or_default().push(position);
+            predecessors.entry(arc.destination).or_default().push(position);
+            edge_positions.insert(arc.index(), position);
+        }
+
+        let mut planner = DStarLite {
+            start,
+            last_start: start,
+            goal,
+            arcs,
+            successors,
+            predecessors,
+            edge_positions,
+            positions,
+            heuristic,
+            g: HashMap::new(),
+            rhs: HashMap::from([(goal, 0.0)]),
+            queue: DoublePriorityQueue::new(),
+            k_m: 0.0,
+            expanded: 0,
+        };
+
+        let key = planner.key(goal);
+        planner.queue.push(goal, key);
+        return planner;
+    }
+
+    pub fn start(&self) -> usize {
+        return self.start;
+    }
+
+    pub fn goal(&self) -> usize {
+        return self.goal;
+    }
+
+    // Number of node expansions over all queries so far.
+    pub fn expanded(&self) -> usize {
+        return self.expanded;
+    }
+
+    pub fn move_start(&mut self, start: usize) {
+        self.k_m += self.h(self.last_start, start);
+        self.last_start = start;
+        self.start = start;
+    }
+
+    pub fn set_edge_cost(&mut self, edge_index: usize, weight: f32) {
+        if let Some(position) = self.edge_positions.get(&edge_index).cloned() {
+            self.set_arc_cost(position, weight);
+        }
+    }
+
+    // Sets the cost of every arc into the cell, which is the cost of entering it.
+    pub fn set_cell_cost(&mut self, node_id: usize, cost: f32) {
+        for position in self.predecessors.get(&node_id).cloned().unwrap_or_default() {
+            self.set_arc_cost(position, cost);
+        }
+    }
+
+    pub fn apply(&mut self, changes: &[CellChange]) {
+        for change in changes {
+            self.set_cell_cost(change.node_id, change.cost);
+        }
+    }
+
+    pub fn distance(&mut self) -> Option<f32> {
+        self.compute_shortest_path();
+
+        let distance = self.g_of(self.start);
+        return if distance.is_finite() { Some(distance) } else { None };
+    }
+
+    // Shortest path from the current start to the goal, empty if the goal is unreachable.
+    pub fn path(&mut self) -> Graph {
+        if self.distance().is_none() {
+            return Graph::from(Vec::new());
+        }
+
+        let mut edges = Vec::new();
+        let mut current = self.start;
+
+        while current != self.goal && edges.len() < self.arcs.len() {
+            let next = self.successors.get(&current).unwrap_or(&Vec::new()).iter()
+                .min_by(|arc1, arc2| self.via(**arc1).total_cmp(&self.via(**arc2)))
+                .cloned();
+
+            match next {
+                None => return Graph::from(Vec::new()),
+                Some(arc) => {
+                    edges.push(self.arcs[arc].clone());
+                    current = self.arcs[arc].destination;
+                }
+            }
+        }
+
+        return Graph::from(edges);
+    }
+
+    fn set_arc_cost(&mut self, position: usize, weight: f32) {
+        self.arcs[position].weight = weight;
+        self.update_vertex(self.arcs[position].source);
+    }
+
+    fn compute_shortest_path(&mut self) {
+        while let Some((node_id, key_old)) = self.queue.peek_min().map(|(node, key)| (*node, *key)) {
+            let start_consistent = self.g_of(self.start) == self.rhs_of(self.start);
+            if key_old >= self.key(self.start) && start_consistent {
+                break;
+            }
+
+            self.queue.pop_min();
+            let key_new = self.key(node_id);
+
+            if key_old < key_new {
+                self.queue.push(node_id, key_new);
+            } else if self.g_of(node_id) > self.rhs_of(node_id) {
+                self.expanded += 1;
+                self.g.insert(node_id, self.rhs_of(node_id));
+
+                for arc in self.predecessors.get(&node_id).cloned().unwrap_or_default() {
+                    self.update_vertex(self.arcs[arc].source);
+                }
+            } else {
+                self.expanded += 1;
+                self.g.insert(node_id, f32::INFINITY);
+
+                for arc in self.predecessors.get(&node_id).cloned().unwrap_or_default() {
+                    self.update_vertex(self.arcs[arc].source);
+                }
+                self.update_vertex(node_id);
+            }
+        }
+    }
+
+    fn update_vertex(&mut self, node_id: usize) {
+        if node_id != self.goal {
+            let rhs = self.successors.get(&node_id).unwrap_or(&Vec::new()).iter()
+                .map(|arc| self.via(*arc))
+                .fold(f32::INFINITY, f32::min);
+            self.rhs.insert(node_id, rhs);
+        }
+
+        self.queue.remove(&node_id);
+        if self.g_of(node_id) != self.rhs_of(node_id) {
+            let key = self.key(node_id);
+            self.queue.push(node_id, key);
+        }
+    }
+
+    fn via(&self, arc: usize) -> f32 {
+        return self.arcs[arc].weight + self.g_of(self.arcs[arc].destination);
+    }
+
+    fn key(&self, node_id: usize) -> Key {
+        let cost = self.g_of(node_id).min(self.rhs_of(node_id));
+        return (NotNan::new(cost + self.h(self.start, node_id) + self.k_m).unwrap(), NotNan::new(cost).unwrap());
+    }
+
+    fn h(&self, from: usize, to: usize) -> f32 {
+        return match &self.positions {
+            None => 0.0,
+            Some(positions) => match (positions.get(&from), positions.get(&to)) {
+                (Some(from), Some(to)) => (self.heuristic)(from, to),
+                _ => 0.0
+            }
+        };
+    }
+
+    fn g_of(&self, node_id: usize) -> f32 {
+        return *self.g.get(&node_id).unwrap_or(&f32::INFINITY);
+    }
+
+    fn rhs_of(&self, node_id: usize) -> f32 {
+        return *self.rhs.get(&node_id).unwrap_or(&f32::INFINITY);
+    }
+}
+
+
+#[cfg(test)]
+fn graph() -> Graph {
+    return Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 4.0),
+        Edge::from(1, 0, 2, 2.0),
+        Edge::from(2, 1, 2, 3.0),
+        Edge::from(3, 1, 3, 2.0),
+        Edge::from(4, 1, 4, 3.0),
+        Edge::from(5, 2, 1, 1.0),
+        Edge::from(6, 2, 3, 4.0),
+        Edge::from(7, 2, 4, 5.0),
+        Edge::from(8, 4, 3, 1.0)
+    ]));
+}
+
+#[cfg(test)]
+fn cost(path: &Graph) -> f32 {
+    return path.edges.iter().map(|edge| edge.weight).sum();
+}
+
+#[cfg(test)]
+fn open_grid() -> Grid {
+    let row: &[f32] = &[1.0; 10];
+    return Grid::from(&[row; 10]);
+}
+
+#[cfg(test)]
+fn straight() -> Vec<Direction> {
+    return vec![Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+}
+
+#[test]
+fn d_star_lite_should_find_shortest_path_in_graph() {
+    let mut planner = DStarLite::from_graph(0, 3, &graph(), Box::new(|_, _| 0.0));
+
+    let path = planner.path();
+
+    assert_eq!(Some(5.0), planner.distance());
+    assert_eq!(5.0, cost(&path));
+    assert_eq!(vec![1, 5, 3], path.edges.iter().map(|edge| edge.index()).collect::<Vec<usize>>());
+}
+
+#[test]
+fn d_star_lite_should_replan_after_edge_cost_change() {
+    let mut planner = DStarLite::from_graph(0, 3, &graph(), Box::new(|_, _| 0.0));
+    planner.path();
+
+    planner.set_edge_cost(5, 10.0);
+    let path = planner.path();
+
+    assert_eq!(6.0, cost(&path));
+    assert!(path.edges.iter().all(|edge| edge.index() != 5));
+
+    planner.set_edge_cost(5, 1.0);
+    assert_eq!(Some(5.0), planner.distance());
+}
+
+#[test]
+fn d_star_lite_should_return_empty_path_if_unreachable() {
+    let mut planner = DStarLite::from_graph(3, 0, &graph(), Box::new(|_, _| 0.0));
+
+    assert_eq!(None, planner.distance());
+    assert!(planner.path().edges.is_empty());
+}
+
+#[test]
+fn d_star_lite_should_follow_moving_start() {
+    let grid = open_grid();
+    let mut planner = DStarLite::from_grid((0, 0), (9, 9), &grid, &straight(), Box::new(|src, dest| src.manhattan_dist(dest)));
+    let path = planner.path();
+    assert_eq!(18.0, cost(&path));
+
+    planner.move_start(path.edges[4].destination);
+    planner.set_cell_cost(grid.node_id((9, 8)), 5.0);
+
+    let expected = Dijkstra {}.grid(grid.coords(planner.start()), (9, 9), &Grid::from(&[
+        &[1.0; 10], &[1.0; 10], &[1.0; 10], &[1.0; 10], &[1.0; 10],
+        &[1.0; 10], &[1.0; 10], &[1.0; 10], &[1.0; 10],
+        &[1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 5.0, 1.0],
+    ]), &straight());
+
+    assert_eq!(cost(&expected), cost(&planner.path()));
+    assert_eq!(13.0, cost(&planner.path()));
+}
+
+#[test]
+fn d_star_lite_should_only_repair_affected_part() {
+    let grid = open_grid();
+    let mut planner = DStarLite::from_grid((0, 0), (9, 9), &grid, &straight(), Box::new(|src, dest| src.manhattan_dist(dest)));
+    let path = planner.path();
+    let initial = planner.expanded();
+
+    // block the first cell on the path
+    let mut grid = grid;
+    grid.block(grid.coords(path.edges[0].destination));
+    planner.apply(grid.changes_since(0).unwrap());
+    let repaired = planner.path();
+
+    assert_eq!(18.0, cost(&repaired));
+    assert_ne!(path.edges[0].destination, repaired.edges[0].destination);
+    assert!(planner.expanded() - initial < initial);
+}
+
+#[test]
+fn d_star_lite_should_change_single_grid_arc() {
+    let grid = Grid::from(&[&[1.0, 1.0, 1.0]]);
+    let mut planner = DStarLite::from_grid((0, 0), (0, 2), &grid, &[Direction::Left, Direction::Right], Box::new(|_, _| 0.0));
+    let indices: Vec<usize> = planner.path().edges.iter().map(|edge| edge.index()).collect();
+
+    // arcs into the middle cell from both sides, only the one on the path becomes expensive
+    planner.set_edge_cost(indices[0], 10.0);
+
+    assert_eq!(vec![0, 2], indices);
+    assert_eq!(Some(11.0), planner.distance());
+    assert_eq!(1.0, planner.arcs.iter().find(|arc| arc.source == 2 && arc.destination == 1).unwrap().weight);
+}
@@ -5,41 +5,66 @@ use priority_queue::DoublePriorityQueue;
 
 use crate::graph::{Edge, Graph};
 use crate::grid::{Direction, Grid};
-use crate::node::{Node, Vec3};
-use crate::path::PathFinding;
+use crate::node::Node;
+use crate::path::{PathFinding, Traced};
+use crate::query;
+use crate::query::QueryOptions;
+use crate::search::expansions::Expansions;
 
 pub struct BreadthFirstSearch {}
 
 pub struct Dijkstra {}
 
+// The heuristic estimates the remaining cost from a node id to the target.
 pub(crate) fn dijkstra(source: Node,
                        target: Node,
                        graph: &Graph,
-                       heuristic: &dyn Fn(&Vec3, &Vec3) -> f32) -> Graph {
+                       heuristic: &dyn Fn(usize) -> f32,
+                       options: &QueryOptions) -> Graph {
+    return dijkstra_traced(source, target, graph, heuristic, options, &mut Expansions::off());
+}
+
+pub(crate) fn dijkstra_traced(source: Node,
+                              target: Node,
+                              graph: &Graph,
+                              heuristic: &dyn Fn(usize) -> f32,
+                              options: &QueryOptions,
+                              expansions: &mut Expansions) -> Graph {
+    if !options.permits_node(source.id) || !options.permits_node(target.id) {
+        return Graph::from(Vec::new());
+    }
+
     let mut visited: HashSet<usize> = HashSet::new();
+    let mut costs: HashMap<usize, f32> = HashMap::new();
     let mut node_to_edges: HashMap<usize, Vec<Edge>> = HashMap::new();
     let mut queue: DoublePriorityQueue<usize, NotNan<f32>> = DoublePriorityQueue::new();
 
-    queue.push(source.id, NotNan::new(0.0).unwrap());
+    queue.push(source.id, NotNan::new(heuristic(source.id)).unwrap());
+    costs.insert(source.id, 0.0);
     node_to_edges.insert(source.id, Vec::new());
 
     while !visited.contains(&target.id) && !queue.is_empty() {
         let current = queue.pop_min().unwrap();
         visited.insert(current.0);
+        expansions.record(current.0);
 
         if let Some(node) = graph.nodes_lookup.get(&current.0) {
             for edge in &node.edges {
+                if !options.permits(edge) {
+                    continue;
+                }
+
                 let dest_id = edge.destination;
 
                 if !visited.contains(&dest_id) {
-                    let mut cost = current.1 + edge.weight;
+                    let cost = costs[&current.0] + edge.weight;
 
-                    if graph.position_is_set() {
-                        cost = cost + heuristic(graph.get_position(&edge.destination),
-                            graph.get_position(&target.id), );
+                    if costs.get(&dest_id).is_some_and(|known| *known <= cost) {
+                        continue;
                     }
 
-                    queue.push(edge.destination, cost);
+                    costs.insert(dest_id, cost);
+                    queue.push(dest_id, NotNan::new(cost + heuristic(dest_id)).unwrap());
 
                     let mut from_edges = node_to_edges.get(&current.0).unwrap_or(&Vec::new()).clone();
                     from_edges.push(edge.clone());
@@ -52,24 +77,37 @@ pub(crate) fn dijkstra(source: Node,
     return Graph::from(node_to_edges.get(&target.id).cloned().unwrap_or_default().into());
 }
 
+// The heuristic estimates the remaining cost from a node id to the target.
 pub(crate) fn dijkstra_grid(source: (usize, usize),
                             target: (usize, usize),
                             grid: &Grid,
                             directions: &[Direction],
-                            heuristic: &dyn Fn(&Vec3, &Vec3) -> f32) -> Graph {
+                            heuristic: &dyn Fn(usize) -> f32) -> Graph {
+    return dijkstra_grid_traced(source, target, grid, directions, heuristic, &mut Expansions::off());
+}
+
+pub(crate) fn dijkstra_grid_traced(source: (usize, usize),
+                                   target: (usize, usize),
+                                   grid: &Grid,
+                                   directions: &[Direction],
+                                   heuristic: &dyn Fn(usize) -> f32,
+                                   expansions: &mut Expansions) -> Graph {
     let mut visited: HashSet<usize> = HashSet::new();
+    let mut costs: HashMap<usize, f32> = HashMap::new();
     let mut node_to_edges: HashMap<usize, Vec<Edge>> = HashMap::new();
     let mut queue: DoublePriorityQueue<usize, NotNan<f32>> = DoublePriorityQueue::new();
 
     let src_id = grid.node_id(source);
     let trg_id = grid.node_id(target);
 
-    queue.push(src_id, NotNan::new(0.0).unwrap());
+    queue.push(src_id, NotNan::new(heuristic(src_id)).unwrap());
+    costs.insert(src_id, 0.0);
     node_to_edges.insert(src_id, Vec::new());
 
     while !visited.contains(&trg_id) && !queue.is_empty() {
         let current = queue.pop_min().unwrap();
         visited.insert(current.0);
+        expansions.record(current.0);
 
         for direction in directions {
             let dest_coord = direction.attempt_move(grid.coords(current.0));
@@ -81,11 +119,14 @@ pub(crate) fn dijkstra_grid(source: (usize, usize),
             let dest_id = grid.node_id(dest_coord);
 
             if !visited.contains(&dest_id) {
-                let cost = current.1 + grid.cost(dest_id) + heuristic(
-                    &Vec3::from(dest_coord.0 as f32, dest_coord.1 as f32, 0.0),
-                    &Vec3::from(target.0 as f32, target.1 as f32, 0.0),
-                );
-                queue.push(dest_id, cost);
+                let cost = costs[&current.0] + grid.cost(dest_id);
+
+                if costs.get(&dest_id).is_some_and(|known| *known <= cost) {
+                    continue;
+                }
+
+                costs.insert(dest_id, cost);
+                queue.push(dest_id, NotNan::new(cost + heuristic(dest_id)).unwrap());
                 let edge = Edge::from(dest_id, current.0, dest_id, grid.cost(dest_id));
 
                 let mut from_edges = node_to_edges.get(&current.0).unwrap_or(&Vec::new()).clone();
@@ -98,18 +139,38 @@ pub(crate) fn dijkstra_grid(source: (usize, usize),
     return Graph::from(node_to_edges.get(&trg_id).cloned().unwrap_or_default().into());
 }
 
-fn dijkstra_heuristic(_src: &Vec3, _dest: &Vec3) -> f32 {
+fn dijkstra_heuristic(_node_id: usize) -> f32 {
     return 0.0;
 }
 
 impl PathFinding for Dijkstra {
     fn graph(&self, source: Node, target: Node, graph: &Graph) -> Graph {
-        return dijkstra(source, target, graph, &dijkstra_heuristic);
+        return dijkstra(source, target, graph, &dijkstra_heuristic, &QueryOptions::new());
+    }
+
+    fn graph_with(&self, source: Node, target: Node, graph: &Graph, options: &QueryOptions) -> Graph {
+        if options.resource.is_some() {
+            return query::resource_constrained(source.id, target.id, graph, options);
+        }
+
+        return dijkstra(source, target, graph, &dijkstra_heuristic, options);
     }
 
     fn grid(&self, source: (usize, usize), target: (usize, usize), grid: &Grid, directions: &[Direction]) -> Graph {
         return dijkstra_grid(source, target, grid, directions, &dijkstra_heuristic);
     }
+
+    fn graph_traced(&self, source: Node, target: Node, graph: &Graph) -> Traced {
+        let mut expansions = Expansions::on();
+        let path = dijkstra_traced(source, target, graph, &dijkstra_heuristic, &QueryOptions::new(), &mut expansions);
+        return Traced { path, expanded: expansions.into_nodes() };
+    }
+
+    fn grid_traced(&self, source: (usize, usize), target: (usize, usize), grid: &Grid, directions: &[Direction]) -> Traced {
+        let mut expansions = Expansions::on();
+        let path = dijkstra_grid_traced(source, target, grid, directions, &dijkstra_heuristic, &mut expansions);
+        return Traced { path, expanded: expansions.into_nodes() };
+    }
 }
 
 #[test]
@@ -171,6 +232,51 @@ fn should_find_path_with_disjoint_graphs() {
     assert_eq!(0, path.edges.len());
 }
 
+#[test]
+fn should_keep_cheaper_queued_path_with_dijkstra() {
+    let graph = Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 3.0),
+        Edge::from(1, 0, 2, 1.0),
+        Edge::from(2, 2, 1, 5.0),
+    ]));
+
+    let dij = Dijkstra {};
+    let path = dij.graph(get_node(0, &graph), get_node(1, &graph), &graph);
+
+    assert_eq!(3.0, calc_cost(&path.edges));
+    assert_eq!(1, path.edges.len());
+}
+
+#[test]
+fn should_find_path_with_dijkstra_avoiding_blocked_node() {
+    let graph = graph();
+    let options = QueryOptions {
+        blocked_nodes: HashSet::from([2]),
+        ..QueryOptions::new()
+    };
+
+    let dij = Dijkstra {};
+    let path = dij.graph_with(get_node(0, &graph), get_node(4, &graph), &graph, &options);
+
+    assert_eq!(7.0, calc_cost(&path.edges));
+    assert_eq!(2, path.edges.len());
+}
+
+#[test]
+fn should_find_path_with_dijkstra_avoiding_blocked_edge() {
+    let graph = graph();
+    let options = QueryOptions {
+        blocked_edges: HashSet::from([5]),
+        ..QueryOptions::new()
+    };
+
+    let dij = Dijkstra {};
+    let path = dij.graph_with(get_node(0, &graph), get_node(1, &graph), &graph, &options);
+
+    assert_eq!(4.0, calc_cost(&path.edges));
+    assert_eq!(1, path.edges.len());
+}
+
 #[cfg(test)]
 fn graph() -> Graph {
     return Graph::from(Vec::from([
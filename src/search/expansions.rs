@@ -0,0 +1,24 @@
+// Node ids taken from the open list, in order. Only collected, if the caller asked for a traced search.
+pub(crate) struct Expansions {
+    nodes: Option<Vec<usize>>,
+}
+
+impl Expansions {
+    pub fn off() -> Expansions {
+        return Expansions { nodes: None };
+    }
+
+    pub fn on() -> Expansions {
+        return Expansions { nodes: Some(Vec::new()) };
+    }
+
+    pub fn record(&mut self, node_id: usize) {
+        if let Some(nodes) = self.nodes.as_mut() {
+            nodes.push(node_id);
+        }
+    }
+
+    pub fn into_nodes(self) -> Option<Vec<usize>> {
+        return self.nodes;
+    }
+}
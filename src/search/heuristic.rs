@@ -0,0 +1,257 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::graph::Graph;
+#[cfg(test)]
+use crate::graph::Edge;
+use crate::grid::{Direction, Grid};
+use crate::node::{Node, Vec3};
+use crate::path::{PathFinding, Traced};
+use crate::query::QueryOptions;
+use crate::search::AStar;
+
+// Heuristics for grids, where cells are positioned at (row, column, 0). Hex distances expect a hex grid in axial
+// coordinates, where the neighbours of a cell are reached with Up, Down, Left, Right, UpRight and DownLeft.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GridHeuristic {
+    Euclidean,
+    Manhattan,
+    Chebyshev,
+    Octile,
+    Hex,
+    Scaled(Box<GridHeuristic>, f32),
+}
+
+impl GridHeuristic {
+    pub fn scaled(self, factor: f32) -> GridHeuristic {
+        return GridHeuristic::Scaled(Box::new(self), factor);
+    }
+
+    pub fn distance(&self, src: &Vec3, dest: &Vec3) -> f32 {
+        return match self {
+            GridHeuristic::Euclidean => src.euclidean_dist(dest),
+            GridHeuristic::Manhattan => src.manhattan_dist(dest),
+            GridHeuristic::Chebyshev => src.chebyshev_dist(dest),
+            GridHeuristic::Octile => src.octile_dist(dest),
+            GridHeuristic::Hex => src.hex_dist(dest),
+            GridHeuristic::Scaled(heuristic, factor) => factor * heuristic.distance(src, dest),
+        };
+    }
+
+    // Largest estimate for a single move. As all heuristics satisfy the triangle inequality, a path of n moves is
+    // never estimated above n times this value.
+    pub fn max_step(&self, directions: &[Direction]) -> f32 {
+        let origin = Vec3::zeroed();
+        return directions.iter()
+            .map(|direction| self.distance(&origin, &step(direction)))
+            .fold(0.0, f32::max);
+    }
+
+    // Moving into a cell costs the cell's cost, for diagonal moves as well. Assumes every cell costs at least one.
+    pub fn admissible(&self, directions: &[Direction]) -> bool {
+        return self.max_step(directions) <= 1.0;
+    }
+
+    pub fn admissible_in(&self, grid: &Grid, directions: &[Direction]) -> bool {
+        return self.max_step(directions) <= minimum_cost(grid);
+    }
+
+    fn to_a_star(&self) -> AStar {
+        let heuristic = self.clone();
+        return AStar { heuristic: Box::new(move |src, dest| heuristic.distance(src, dest)) };
+    }
+}
+
+fn step(direction: &Direction) -> Vec3 {
+    let (row, col) = match direction {
+        Direction::Up => (-1.0, 0.0),
+        Direction::Down => (1.0, 0.0),
+        Direction::Left => (0.0, -1.0),
+        Direction::Right => (0.0, 1.0),
+        Direction::UpLeft => (-1.0, -1.0),
+        Direction::UpRight => (-1.0, 1.0),
+        Direction::DownLeft => (1.0, -1.0),
+        Direction::DownRight => (1.0, 1.0),
+    };
+
+    return Vec3::from(row, col, 0.0);
+}
+
+fn minimum_cost(grid: &Grid) -> f32 {
+    return grid.costs.iter().flatten().cloned().fold(f32::INFINITY, f32::min);
+}
+
+// What a grid search does, if the heuristic may overestimate for the grid and directions. Use GridAStar::check to find
+// out beforehand.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Inadmissible {
+    Accept,
+    // Scales the heuristic down until it is admissible.
+    Fallback,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GridAStar {
+    pub heuristic: GridHeuristic,
+    pub inadmissible: Inadmissible,
+}
+
+impl GridAStar {
+    // The factor the heuristic overestimates by at most, if it is not admissible for the grid and directions.
+    pub fn check(&self, grid: &Grid, directions: &[Direction]) -> Result<(), f32> {
+        let (max_step, minimum_cost) = (self.heuristic.max_step(directions), minimum_cost(grid));
+        return if max_step <= minimum_cost { Ok(()) } else { Err(max_step / minimum_cost.max(0.0)) };
+    }
+
+    pub fn heuristic_for(&self, grid: &Grid, directions: &[Direction]) -> GridHeuristic {
+        let max_step = self.heuristic.max_step(directions);
+        let minimum_cost = minimum_cost(grid);
+
+        if max_step <= minimum_cost {
+            return self.heuristic.clone();
+        }
+
+        return match self.inadmissible {
+            Inadmissible::Accept => self.heuristic.clone(),
+            Inadmissible::Fallback => self.heuristic.clone().scaled(minimum_cost.max(0.0) / max_step),
+        };
+    }
+}
+
+impl PathFinding for GridAStar {
+    fn graph(&self, source: Node, target: Node, graph: &Graph) -> Graph {
+        return self.heuristic.to_a_star().graph(source, target, graph);
+    }
+
+    fn graph_with(&self, source: Node, target: Node, graph: &Graph, options: &QueryOptions) -> Graph {
+        return self.heuristic.to_a_star().graph_with(source, target, graph, options);
+    }
+
+    fn grid(&self, source: (usize, usize), target: (usize, usize), grid: &Grid, directions: &[Direction]) -> Graph {
+        return self.heuristic_for(grid, directions).to_a_star().grid(source, target, grid, directions);
+    }
+
+    fn graph_traced(&self, source: Node, target: Node, graph: &Graph) -> Traced {
+        return self.heuristic.to_a_star().graph_traced(source, target, graph);
+    }
+
+    fn grid_traced(&self, source: (usize, usize), target: (usize, usize), grid: &Grid, directions: &[Direction]) -> Traced {
+        return self.heuristic_for(grid, directions).to_a_star().grid_traced(source, target, grid, directions);
+    }
+}
+
+
+#[cfg(test)]
+fn straight() -> Vec<Direction> {
+    return vec![Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+}
+
+#[cfg(test)]
+fn all() -> Vec<Direction> {
+    return vec![Direction::Up, Direction::Down, Direction::Left, Direction::Right,
+                Direction::UpLeft, Direction::UpRight, Direction::DownLeft, Direction::DownRight];
+}
+
+#[cfg(test)]
+fn hex() -> Vec<Direction> {
+    return vec![Direction::Up, Direction::Down, Direction::Left, Direction::Right,
+                Direction::UpRight, Direction::DownLeft];
+}
+
+#[cfg(test)]
+fn cost(path: &Graph) -> f32 {
+    return path.edges.iter().map(|edge| edge.weight).sum();
+}
+
+#[test]
+fn heuristics_should_declare_admissibility_for_directions() {
+    for heuristic in [GridHeuristic::Euclidean, GridHeuristic::Manhattan, GridHeuristic::Chebyshev,
+        GridHeuristic::Octile, GridHeuristic::Hex] {
+        assert!(heuristic.admissible(&straight()));
+    }
+
+    assert!(GridHeuristic::Chebyshev.admissible(&all()));
+    assert!(!GridHeuristic::Manhattan.admissible(&all()));
+    assert!(!GridHeuristic::Euclidean.admissible(&all()));
+    assert!(!GridHeuristic::Octile.admissible(&all()));
+    assert!(!GridHeuristic::Hex.admissible(&all()));
+
+    assert!(GridHeuristic::Hex.admissible(&hex()));
+    assert!(!GridHeuristic::Manhattan.admissible(&hex()));
+}
+
+#[test]
+fn scaled_heuristic_should_respect_minimum_cost() {
+    let grid = Grid::from(&[
+        &[2.0, 3.0],
+        &[4.0, 2.0],
+    ]);
+
+    assert!(GridHeuristic::Manhattan.scaled(2.0).admissible_in(&grid, &straight()));
+    assert!(!GridHeuristic::Manhattan.scaled(2.5).admissible_in(&grid, &straight()));
+    assert!(!GridHeuristic::Manhattan.scaled(2.0).admissible(&straight()));
+    assert_eq!(4.0, GridHeuristic::Chebyshev.scaled(2.0).distance(&Vec3::zeroed(), &Vec3::from(1.0, 2.0, 0.0)));
+}
+
+#[test]
+fn fallback_should_scale_down_inadmissible_heuristic() {
+    let grid = Grid::from(&[
+        &[1.0, 1.0, 1.0],
+        &[1.0, 5.0, 1.0],
+        &[1.0, 1.0, 1.0],
+    ]);
+    let heuristic = GridHeuristic::Manhattan.scaled(10.0);
+
+    let accepting = GridAStar { heuristic: heuristic.clone(), inadmissible: Inadmissible::Accept };
+    let falling_back = GridAStar { heuristic, inadmissible: Inadmissible::Fallback };
+
+    assert_eq!(6.0, cost(&accepting.grid((1, 0), (1, 2), &grid, &straight())));
+    assert_eq!(4.0, cost(&falling_back.grid((1, 0), (1, 2), &grid, &straight())));
+    assert!(falling_back.heuristic_for(&grid, &straight()).admissible_in(&grid, &straight()));
+}
+
+#[test]
+fn check_should_report_overestimation() {
+    let grid = Grid::from(&[
+        &[2.0, 3.0],
+        &[4.0, 2.0],
+    ]);
+    let admissible = GridAStar { heuristic: GridHeuristic::Chebyshev.scaled(2.0), inadmissible: Inadmissible::Accept };
+    let overestimating = GridAStar { heuristic: GridHeuristic::Manhattan.scaled(2.0), inadmissible: Inadmissible::Accept };
+
+    assert_eq!(Ok(()), admissible.check(&grid, &all()));
+    assert_eq!(Err(2.0), overestimating.check(&grid, &all()));
+    assert_eq!(Ok(()), overestimating.check(&grid, &straight()));
+}
+
+#[test]
+fn admissible_heuristic_should_not_be_changed() {
+    let grid = Grid::from(&[
+        &[1.0, 1.0],
+        &[1.0, 1.0],
+    ]);
+    let a_star = GridAStar { heuristic: GridHeuristic::Chebyshev, inadmissible: Inadmissible::Fallback };
+
+    assert_eq!(GridHeuristic::Chebyshev, a_star.heuristic_for(&grid, &all()));
+}
+
+#[test]
+fn grid_a_star_should_search_graph_with_positions() {
+    let mut graph = Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 1.0),
+        Edge::from(1, 1, 2, 1.0),
+        Edge::from(2, 0, 2, 3.0),
+    ]));
+    graph.offer_positions(std::collections::HashMap::from([
+        (0, Vec3::from(0.0, 0.0, 0.0)),
+        (1, Vec3::from(0.0, 1.0, 0.0)),
+        (2, Vec3::from(0.0, 2.0, 0.0)),
+    ]));
+    let a_star = GridAStar { heuristic: GridHeuristic::Octile, inadmissible: Inadmissible::Accept };
+
+    let path = a_star.graph(graph.nodes_lookup[&0].clone(), graph.nodes_lookup[&2].clone(), &graph);
+
+    assert_eq!(2.0, cost(&path));
+}
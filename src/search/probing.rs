@@ -5,6 +5,7 @@ use crate::grid::{Direction, Grid};
 use crate::path;
 use crate::path::Waypoint;
 use crate::search::cost;
+use crate::search::expansions::Expansions;
 
 pub(crate) type Callback = fn(list: &mut VecDeque<Waypoint>) -> Option<Waypoint>;
 
@@ -16,11 +17,13 @@ pub(crate) fn dequeue(queue: &mut VecDeque<Waypoint>) -> Option<Waypoint> {
     return queue.pop_front();
 }
 
-pub(crate) fn probe_graph(start: usize, target: usize, graph: &Graph, control_flow: Callback) -> Graph {
+pub(crate) fn probe_graph(start: usize, target: usize, graph: &Graph, control_flow: Callback,
+                          expansions: &mut Expansions) -> Graph {
     let mut deque = VecDeque::from([Waypoint::from(None, start, None)]);
     let mut visited: HashSet<usize> = HashSet::new();
 
     while let Some(current) = control_flow(&mut deque) {
+        expansions.record(current.node_id);
         if let Some(node) = graph.nodes_lookup.get(&current.node_id) {
             let edges = node.edges.clone();
             visited.insert(current.node_id);
@@ -49,7 +52,8 @@ pub(crate) fn probe_graph(start: usize, target: usize, graph: &Graph, control_fl
 }
 
 pub(crate) fn probe_grid(start_coord: (usize, usize), target_coord: (usize, usize),
-                         grid: &Grid, directions: &[Direction], control_flow: Callback) -> Graph {
+                         grid: &Grid, directions: &[Direction], control_flow: Callback,
+                         expansions: &mut Expansions) -> Graph {
     let start = grid.node_id(start_coord);
     let target = grid.node_id(target_coord);
 
@@ -57,6 +61,7 @@ pub(crate) fn probe_grid(start_coord: (usize, usize), target_coord: (usize, usiz
     let mut visited: HashMap<usize, Waypoint> = HashMap::new();
 
     while let Some(current) = control_flow(&mut deque) {
+        expansions.record(current.node_id);
         visited.insert(current.node_id, current.clone());
 
         if let Some(result) = go_directions(&mut deque, current, grid, directions, &visited, target) {
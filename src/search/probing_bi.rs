@@ -5,10 +5,11 @@ use crate::grid::{Direction, Grid};
 use crate::node::Node;
 use crate::path;
 use crate::path::Waypoint;
+use crate::search::expansions::Expansions;
 use crate::search::probing::go_directions;
 
 pub(crate) fn probe_grid(start_coord: (usize, usize), target_coord: (usize, usize),
-                         grid: &Grid, dirs: &[Direction]) -> Graph {
+                         grid: &Grid, dirs: &[Direction], expansions: &mut Expansions) -> Graph {
     let start = grid.node_id(start_coord);
     let target = grid.node_id(target_coord);
 
@@ -20,12 +21,12 @@ pub(crate) fn probe_grid(start_coord: (usize, usize), target_coord: (usize, usiz
 
     while !start_queue.is_empty() || !target_queue.is_empty() {
         if let Some(start_result) = process_dequeue(start_queue, grid, dirs, &mut start_vis,
-                                                    &mut target_vis, target) {
+                                                    &mut target_vis, target, expansions) {
             return start_result;
         }
 
         if let Some(end_result) = process_dequeue(target_queue, grid, dirs, &mut target_vis,
-                                                  &mut start_vis, start) {
+                                                  &mut start_vis, start, expansions) {
             return end_result;
         }
     }
@@ -40,8 +41,10 @@ fn process_dequeue(
     visited: &mut HashMap<usize, Waypoint>,
     other_visited: &mut HashMap<usize, Waypoint>,
     target: usize,
+    expansions: &mut Expansions,
 ) -> Option<Graph> {
     if let Some(current) = deque.pop_front() {
+        expansions.record(current.node_id);
         let current_id = current.node_id;
         visited.insert(current_id, current.clone());
 
@@ -66,7 +69,7 @@ fn process_dequeue(
     None
 }
 
-pub(crate) fn probe_graph(start: Node, target: Node, graph: &Graph) -> Graph {
+pub(crate) fn probe_graph(start: Node, target: Node, graph: &Graph, expansions: &mut Expansions) -> Graph {
     let start_queue = &mut VecDeque::from([Waypoint::from(None, start.id, None)]);
     let target_queue = &mut VecDeque::from([Waypoint::from(None, target.id, None)]);
 
@@ -75,12 +78,12 @@ pub(crate) fn probe_graph(start: Node, target: Node, graph: &Graph) -> Graph {
 
     while !start_queue.is_empty() || !target_queue.is_empty() {
         if let Some(result_start) = process_node(start_queue, &mut start_visited,
-                                                 &mut target_visited, &target, graph) {
+                                                 &mut target_visited, &target, graph, expansions) {
             return Graph::from(result_start);
         }
 
         if let Some(result_target) = process_node(target_queue, &mut target_visited,
-                                                  &mut start_visited, &start, graph) {
+                                                  &mut start_visited, &start, graph, expansions) {
             return Graph::from(result_target);
         }
     }
@@ -90,8 +93,9 @@ pub(crate) fn probe_graph(start: Node, target: Node, graph: &Graph) -> Graph {
 
 fn process_node(queue: &mut VecDeque<Waypoint>, visited: &mut HashMap<usize, Waypoint>,
                 end_visited: &mut HashMap<usize, Waypoint>,
-                end: &Node, graph: &Graph, ) -> Option<Vec<Edge>> {
+                end: &Node, graph: &Graph, expansions: &mut Expansions) -> Option<Vec<Edge>> {
     if let Some(current) = queue.pop_front() {
+        expansions.record(current.node_id);
         let result = process_edges(queue, &current, end.id, graph, &visited, &end_visited);
         visited.insert(current.node_id, current);
         result
@@ -0,0 +1,356 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use ordered_float::NotNan;
+use priority_queue::DoublePriorityQueue;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::graph::{Edge, Graph};
+use crate::union_find::UnionFind;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Spanning {
+    Minimum,
+    Maximum,
+}
+
+impl Spanning {
+    fn compare(&self, weight1: f32, weight2: f32) -> Ordering {
+        return match self {
+            Spanning::Minimum => weight1.total_cmp(&weight2),
+            Spanning::Maximum => weight2.total_cmp(&weight1),
+        };
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SpanningTree {
+    pub nodes: Vec<usize>,
+    pub graph: Graph,
+    pub total_weight: f32,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SpanningForest {
+    pub trees: Vec<SpanningTree>,
+}
+
+impl SpanningForest {
+    pub fn total_weight(&self) -> f32 {
+        return self.trees.iter().map(|tree| tree.total_weight).sum();
+    }
+
+    pub fn edges(&self) -> Vec<Edge> {
+        return self.trees.iter().flat_map(|tree| tree.graph.edges.clone()).collect();
+    }
+
+    pub fn to_graph(&self) -> Graph {
+        return Graph::from(self.edges());
+    }
+}
+
+// Maps (possibly sparse) node ids to dense indices, so they can be used with the union find.
+pub(crate) struct NodeIndex {
+    pub ids: Vec<usize>,
+    pub lookup: HashMap<usize, usize>,
+}
+
+impl NodeIndex {
+    pub fn from(graph: &Graph) -> NodeIndex {
+        let mut ids: Vec<usize> = graph.nodes_lookup.keys().cloned().collect();
+        ids.sort();
+
+        let lookup = ids.iter().enumerate().map(|(index, id)| (*id, index)).collect();
+
+        return NodeIndex { ids, lookup };
+    }
+
+    pub fn index(&self, node_id: usize) -> usize {
+        return self.lookup[&node_id];
+    }
+}
+
+pub fn kruskal(graph: &Graph, spanning: Spanning) -> SpanningForest {
+    let node_index = NodeIndex::from(graph);
+    let mut union_find = UnionFind::from(node_index.ids.len());
+    let mut edges: Vec<&Edge> = graph.edges.iter().filter(|edge| !edge.weight.is_nan()).collect();
+    edges.sort_by(|edge1, edge2| spanning.compare(edge1.weight, edge2.weight));
+
+    let mut tree_edges = Vec::new();
+    for edge in edges {
+        let (source, destination) = (node_index.index(edge.source), node_index.index(edge.destination));
+
+        if !union_find.connected(source, destination) {
+            union_find.unify(source, destination);
+            tree_edges.push(edge.clone());
+        }
+    }
+
+    return forest(tree_edges, &node_index, &mut union_find);
+}
+
+pub fn prim(graph: &Graph, spanning: Spanning) -> SpanningForest {
+    let node_index = NodeIndex::from(graph);
+    let mut union_find = UnionFind::from(node_index.ids.len());
+    let adjacency = undirected_adjacency(graph);
+
+    let mut visited: HashSet<usize> = HashSet::new();
+    let mut tree_edges = Vec::new();
+
+    for start in &node_index.ids {
+        if visited.contains(start) {
+            continue;
+        }
+
+        let mut queue: DoublePriorityQueue<usize, NotNan<f32>> = DoublePriorityQueue::new();
+        visit(*start, &adjacency, graph, &visited, &mut queue);
+        visited.insert(*start);
+
+        while let Some((position, _)) = next_edge(&mut queue, spanning) {
+            let edge = &graph.edges[position];
+            let unvisited = [edge.source, edge.destination].into_iter()
+                .find(|node_id| !visited.contains(node_id));
+
+            if let Some(node_id) = unvisited {
+                visited.insert(node_id);
+                union_find.unify(node_index.index(edge.source), node_index.index(edge.destination));
+                tree_edges.push(edge.clone());
+                visit(node_id, &adjacency, graph, &visited, &mut queue);
+            }
+        }
+    }
+
+    return forest(tree_edges, &node_index, &mut union_find);
+}
+
+pub fn boruvka(graph: &Graph, spanning: Spanning) -> SpanningForest {
+    let node_index = NodeIndex::from(graph);
+    let mut union_find = UnionFind::from(node_index.ids.len());
+    let mut tree_edges = Vec::new();
+
+    loop {
+        let mut cheapest: HashMap<usize, usize> = HashMap::new();
+
+        for (position, edge) in graph.edges.iter().enumerate() {
+            let source_root = union_find.find(node_index.index(edge.source));
+            let destination_root = union_find.find(node_index.index(edge.destination));
+
+            if source_root == destination_root || edge.weight.is_nan() {
+                continue;
+            }
+
+            for root in [source_root, destination_root] {
+                let better = match cheapest.get(&root) {
+                    None => true,
+                    Some(current) => {
+                        let current_weight = graph.edges[*current].weight;
+                        spanning.compare(edge.weight, current_weight)
+                            .then(position.cmp(current)) == Ordering::Less
+                    }
+                };
+
+                if better {
+                    cheapest.insert(root, position);
+                }
+            }
+        }
+
+        if cheapest.is_empty() {
+            break;
+        }
+
+        let mut positions: Vec<usize> = cheapest.into_values().collect();
+        positions.sort();
+        positions.dedup();
+
+        for position in positions {
+            let edge = &graph.edges[position];
+            let (source, destination) = (node_index.index(edge.source), node_index.index(edge.destination));
+
+            if !union_find.connected(source, destination) {
+                union_find.unify(source, destination);
+                tree_edges.push(edge.clone());
+            }
+        }
+    }
+
+    return forest(tree_edges, &node_index, &mut union_find);
+}
+
+fn undirected_adjacency(graph: &Graph) -> HashMap<usize, Vec<usize>> {
+    let mut adjacency: HashMap<usize, Vec<usize>> = HashMap::new();
+
+    for (position, edge) in graph.edges.iter().enumerate() {
+        adjacency.entry(edge.source).or_default().push(position);
+        adjacency.entry(edge.destination).or_default().push(position);
+    }
+
+    return adjacency;
+}
+
+fn visit(node_id: usize,
+         adjacency: &HashMap<usize, Vec<usize>>,
+         graph: &Graph,
+         visited: &HashSet<usize>,
+         queue: &mut DoublePriorityQueue<usize, NotNan<f32>>) {
+    for position in adjacency.get(&node_id).unwrap_or(&Vec::new()) {
+        let edge = &graph.edges[*position];
+        let other = if edge.source == node_id { edge.destination } else { edge.source };
+
+        // edges without a comparable weight are never part of a tree
+        if let (false, Ok(weight)) = (visited.contains(&other), NotNan::new(edge.weight)) {
+            queue.push(*position, weight);
+        }
+    }
+}
+
+fn next_edge(queue: &mut DoublePriorityQueue<usize, NotNan<f32>>, spanning: Spanning) -> Option<(usize, NotNan<f32>)> {
+    return match spanning {
+        Spanning::Minimum => queue.pop_min(),
+        Spanning::Maximum => queue.pop_max(),
+    };
+}
+
+fn forest(tree_edges: Vec<Edge>, node_index: &NodeIndex, union_find: &mut UnionFind) -> SpanningForest {
+    let mut roots: Vec<usize> = Vec::new();
+    let mut nodes_by_root: HashMap<usize, Vec<usize>> = HashMap::new();
+
+    for (index, node_id) in node_index.ids.iter().enumerate() {
+        let root = union_find.find(index);
+        if !nodes_by_root.contains_key(&root) {
+            roots.push(root);
+        }
+        nodes_by_root.entry(root).or_default().push(*node_id);
+    }
+
+    let mut edges_by_root: HashMap<usize, Vec<Edge>> = HashMap::new();
+    for edge in tree_edges {
+        let root = union_find.find(node_index.index(edge.source));
+        edges_by_root.entry(root).or_default().push(edge);
+    }
+
+    let trees = roots.iter().map(|root| {
+        let edges = edges_by_root.remove(root).unwrap_or_default();
+        let total_weight = edges.iter().map(|edge| edge.weight).sum();
+
+        SpanningTree {
+            nodes: nodes_by_root.remove(root).unwrap_or_default(),
+            graph: Graph::from(edges),
+            total_weight,
+        }
+    }).collect();
+
+    return SpanningForest { trees };
+}
+
+
+#[cfg(test)]
+fn graph() -> Graph {
+    return Graph::from(Vec::from([
+        Edge::from(0, 1, 2, 1.0),
+        Edge::from(1, 2, 3, 2.0),
+        Edge::from(2, 1, 0, 3.0),
+        Edge::from(3, 3, 4, 3.0),
+        Edge::from(4, 1, 3, 4.0),
+        Edge::from(5, 0, 3, 6.0),
+        Edge::from(6, 0, 4, 7.0),
+    ]));
+}
+
+#[cfg(test)]
+fn disjoint_sparse_graph() -> Graph {
+    return Graph::from(Vec::from([
+        Edge::from(0, 10, 20, 1.0),
+        Edge::from(1, 20, 30, 2.0),
+        Edge::from(2, 10, 30, 5.0),
+        Edge::from(3, 100, 200, 4.0),
+        Edge::from(4, 200, 300, 1.0),
+        Edge::from(5, 300, 100, 3.0),
+    ]));
+}
+
+#[test]
+fn kruskal_should_return_minimum_spanning_tree() {
+    let forest = kruskal(&graph(), Spanning::Minimum);
+
+    assert_eq!(1, forest.trees.len());
+    assert_eq!(4, forest.edges().len());
+    assert_eq!(9.0, forest.total_weight());
+}
+
+#[test]
+fn prim_should_return_minimum_spanning_tree() {
+    let forest = prim(&graph(), Spanning::Minimum);
+
+    assert_eq!(1, forest.trees.len());
+    assert_eq!(4, forest.edges().len());
+    assert_eq!(9.0, forest.total_weight());
+}
+
+#[test]
+fn boruvka_should_return_minimum_spanning_tree() {
+    let forest = boruvka(&graph(), Spanning::Minimum);
+
+    assert_eq!(1, forest.trees.len());
+    assert_eq!(4, forest.edges().len());
+    assert_eq!(9.0, forest.total_weight());
+}
+
+#[test]
+fn maximum_spanning_tree_should_be_equal_for_all_algorithms() {
+    let graph = graph();
+
+    assert_eq!(19.0, kruskal(&graph, Spanning::Maximum).total_weight());
+    assert_eq!(19.0, prim(&graph, Spanning::Maximum).total_weight());
+    assert_eq!(19.0, boruvka(&graph, Spanning::Maximum).total_weight());
+}
+
+#[test]
+fn spanning_forest_should_have_tree_per_component() {
+    let graph = disjoint_sparse_graph();
+
+    for forest in [kruskal(&graph, Spanning::Minimum),
+        prim(&graph, Spanning::Minimum),
+        boruvka(&graph, Spanning::Minimum)] {
+        assert_eq!(2, forest.trees.len());
+        assert_eq!(vec![10, 20, 30], forest.trees[0].nodes);
+        assert_eq!(3.0, forest.trees[0].total_weight);
+        assert_eq!(vec![100, 200, 300], forest.trees[1].nodes);
+        assert_eq!(4.0, forest.trees[1].total_weight);
+        assert_eq!(7.0, forest.total_weight());
+    }
+}
+
+#[test]
+fn spanning_forest_of_empty_graph_should_be_empty() {
+    let forest = prim(&Graph::from(Vec::new()), Spanning::Minimum);
+
+    assert!(forest.trees.is_empty());
+    assert_eq!(0.0, forest.total_weight());
+}
+
+#[test]
+fn spanning_forest_to_graph_should_contain_all_tree_edges() {
+    let graph = kruskal(&disjoint_sparse_graph(), Spanning::Maximum).to_graph();
+
+    assert_eq!(4, graph.edges.len());
+    assert_eq!(6, graph.node_count);
+}
+
+#[test]
+fn spanning_trees_should_skip_nan_weights() {
+    let graph = Graph::from(Vec::from([
+        Edge::from(0, 0, 1, f32::NAN),
+        Edge::from(1, 1, 2, 2.0),
+        Edge::from(2, 0, 2, 4.0),
+    ]));
+
+    for algorithm in [kruskal, prim, boruvka] {
+        let forest = algorithm(&graph, Spanning::Minimum);
+
+        assert_eq!(6.0, forest.total_weight());
+        assert!(forest.edges().iter().all(|edge| !edge.weight.is_nan()));
+    }
+}
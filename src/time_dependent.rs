@@ -0,0 +1,278 @@
+use std::collections::{HashMap, HashSet};
+
+use ordered_float::NotNan;
+use priority_queue::DoublePriorityQueue;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::graph::{Edge, Graph};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TravelTimeError {
+    Empty,
+    Unsorted,
+    Negative,
+    // NaN or infinite departure or travel time.
+    NotFinite,
+    // Departing later along the given segment would arrive earlier.
+    NotFifo(usize),
+}
+
+// Piecewise-linear travel time by departure time, given as (departure, travel time) points. Before the first and after
+// the last point, the travel time stays constant.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(try_from = "TravelTimeData"))]
+pub struct TravelTime {
+    points: Vec<(f32, f32)>,
+}
+
+// Read points go through the same checks as TravelTime::from.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct TravelTimeData {
+    points: Vec<(f32, f32)>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<TravelTimeData> for TravelTime {
+    type Error = String;
+
+    fn try_from(data: TravelTimeData) -> Result<TravelTime, String> {
+        return TravelTime::from(data.points).map_err(|error| format!("invalid travel time: {error:?}"));
+    }
+}
+
+impl TravelTime {
+    pub fn constant(travel_time: f32) -> TravelTime {
+        return TravelTime { points: vec![(0.0, travel_time)] };
+    }
+
+    // FIFO requires the travel time to decrease by at most the time waited, so the slope between points is at least -1.
+    pub fn from(points: Vec<(f32, f32)>) -> Result<TravelTime, TravelTimeError> {
+        if points.is_empty() {
+            return Err(TravelTimeError::Empty);
+        }
+
+        if points.iter().any(|(departure, travel_time)| !departure.is_finite() || !travel_time.is_finite()) {
+            return Err(TravelTimeError::NotFinite);
+        }
+
+        if points.iter().any(|(_, travel_time)| *travel_time < 0.0) {
+            return Err(TravelTimeError::Negative);
+        }
+
+        for (segment, pair) in points.windows(2).enumerate() {
+            let ((departure1, travel_time1), (departure2, travel_time2)) = (pair[0], pair[1]);
+
+            if departure2 <= departure1 {
+                return Err(TravelTimeError::Unsorted);
+            }
+            if departure2 + travel_time2 < departure1 + travel_time1 {
+                return Err(TravelTimeError::NotFifo(segment));
+            }
+        }
+
+        return Ok(TravelTime { points });
+    }
+
+    pub fn points(&self) -> &[(f32, f32)] {
+        return &self.points;
+    }
+
+    pub fn travel_time(&self, departure: f32) -> f32 {
+        let next = self.points.partition_point(|(time, _)| *time <= departure);
+
+        if next == 0 {
+            return self.points[0].1;
+        }
+        if next == self.points.len() {
+            return self.points[next - 1].1;
+        }
+
+        let ((departure1, travel_time1), (departure2, travel_time2)) = (self.points[next - 1], self.points[next]);
+        let share = (departure - departure1) / (departure2 - departure1);
+        return travel_time1 + share * (travel_time2 - travel_time1);
+    }
+
+    pub fn arrival(&self, departure: f32) -> f32 {
+        return departure + self.travel_time(departure);
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimedRoute {
+    pub path: Graph,
+    // Every node along the path with its arrival time, starting with the source at the departure time.
+    pub arrivals: Vec<(usize, f32)>,
+}
+
+impl TimedRoute {
+    pub fn arrival(&self) -> f32 {
+        return self.arrivals.last().unwrap().1;
+    }
+}
+
+// Earliest arrival search. Edges without a travel time function take their weight as constant travel time, edges with
+// a NaN weight are skipped. A NaN departure has no earliest arrival.
+pub fn time_dependent_dijkstra(graph: &Graph,
+                               travel_times: &HashMap<usize, TravelTime>,
+                               source: usize,
+                               target: usize,
+                               departure: f32) -> Option<TimedRoute> {
+    if !graph.nodes_lookup.contains_key(&source) || !graph.nodes_lookup.contains_key(&target) || departure.is_nan() {
+        return None;
+    }
+
+    let mut visited: HashSet<usize> = HashSet::new();
+    let mut arrivals: HashMap<usize, f32> = HashMap::from([(source, departure)]);
+    let mut incoming: HashMap<usize, Edge> = HashMap::new();
+    let mut queue: DoublePriorityQueue<usize, NotNan<f32>> = DoublePriorityQueue::new();
+    queue.push(source, NotNan::new(departure).unwrap());
+
+    while let Some((node_id, arrival)) = queue.pop_min() {
+        visited.insert(node_id);
+
+        if node_id == target {
+            break;
+        }
+
+        for edge in &graph.nodes_lookup[&node_id].edges {
+            if visited.contains(&edge.destination) {
+                continue;
+            }
+
+            let next_arrival = match travel_times.get(&edge.index()) {
+                None => arrival.into_inner() + edge.weight,
+                Some(travel_time) => travel_time.arrival(arrival.into_inner())
+            };
+
+            let priority = match NotNan::new(next_arrival) {
+                Err(_) => continue,
+                Ok(priority) => priority
+            };
+            if arrivals.get(&edge.destination).is_some_and(|known| *known <= next_arrival) {
+                continue;
+            }
+
+            arrivals.insert(edge.destination, next_arrival);
+            incoming.insert(edge.destination, edge.clone());
+            queue.push(edge.destination, priority);
+        }
+    }
+
+    if !visited.contains(&target) {
+        return None;
+    }
+
+    let mut edges = Vec::new();
+    let mut route = vec![(target, arrivals[&target])];
+    let mut current = target;
+
+    while let Some(edge) = incoming.get(&current).filter(|_| current != source) {
+        edges.push(edge.clone());
+        current = edge.source;
+        route.push((current, arrivals[&current]));
+    }
+
+    edges.reverse();
+    route.reverse();
+    return Some(TimedRoute { path: Graph::from(edges), arrivals: route });
+}
+
+
+#[cfg(test)]
+fn graph() -> Graph {
+    // a ferry 0 -> 2 competing with the road 0 -> 1 -> 2
+    return Graph::from(Vec::from([
+        Edge::from(0, 0, 1, 30.0),
+        Edge::from(1, 1, 2, 30.0),
+        Edge::from(2, 0, 2, 0.0),
+        Edge::from(3, 2, 3, 5.0),
+    ]));
+}
+
+#[cfg(test)]
+fn ferry() -> TravelTime {
+    // the ferry leaves at 10 and 70, waiting for it plus a crossing of 20
+    return TravelTime::from(vec![(0.0, 30.0), (10.0, 20.0), (10.5, 79.5), (70.0, 20.0)]).unwrap();
+}
+
+#[test]
+fn travel_time_should_interpolate_and_extend() {
+    let travel_time = TravelTime::from(vec![(10.0, 5.0), (20.0, 10.0)]).unwrap();
+
+    assert_eq!(5.0, travel_time.travel_time(0.0));
+    assert_eq!(7.5, travel_time.travel_time(15.0));
+    assert_eq!(10.0, travel_time.travel_time(100.0));
+    assert_eq!(22.5, travel_time.arrival(15.0));
+}
+
+#[test]
+fn travel_time_should_reject_invalid_points() {
+    assert_eq!(Err(TravelTimeError::Empty), TravelTime::from(vec![]));
+    assert_eq!(Err(TravelTimeError::Unsorted), TravelTime::from(vec![(5.0, 1.0), (5.0, 2.0)]));
+    assert_eq!(Err(TravelTimeError::Negative), TravelTime::from(vec![(5.0, -1.0)]));
+    assert_eq!(Err(TravelTimeError::NotFifo(1)), TravelTime::from(vec![(0.0, 5.0), (1.0, 5.0), (2.0, 1.0)]));
+    assert_eq!(Err(TravelTimeError::NotFinite), TravelTime::from(vec![(0.0, 5.0), (f32::NAN, 5.0)]));
+    assert_eq!(Err(TravelTimeError::NotFinite), TravelTime::from(vec![(0.0, f32::NAN)]));
+    assert_eq!(Err(TravelTimeError::NotFinite), TravelTime::from(vec![(0.0, f32::INFINITY)]));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn travel_time_deserialization_should_check_points() {
+    let read = |json: &str| serde_json::from_str::<TravelTime>(json).map_err(|error| error.to_string());
+
+    assert_eq!(Ok(ferry()), read(&serde_json::to_string(&ferry()).unwrap()));
+    assert!(read("{\"points\":[]}").is_err());
+    assert!(read("{\"points\":[[0.0,5.0],[1.0,5.0],[2.0,1.0]]}").unwrap_err().contains("NotFifo(1)"));
+    assert!(read("{\"points\":[[0.0,1e39]]}").unwrap_err().contains("NotFinite"));
+}
+
+#[test]
+fn time_dependent_dijkstra_should_depend_on_departure() {
+    let graph = graph();
+    let travel_times = HashMap::from([(2, ferry())]);
+
+    let early = time_dependent_dijkstra(&graph, &travel_times, 0, 3, 5.0).unwrap();
+    let late = time_dependent_dijkstra(&graph, &travel_times, 0, 3, 20.0).unwrap();
+
+    assert_eq!(vec![(0, 5.0), (2, 30.0), (3, 35.0)], early.arrivals);
+    assert_eq!(vec![2, 3], early.path.edges.iter().map(|edge| edge.index()).collect::<Vec<usize>>());
+    assert_eq!(vec![(0, 20.0), (1, 50.0), (2, 80.0), (3, 85.0)], late.arrivals);
+    assert_eq!(85.0, late.arrival());
+}
+
+#[test]
+fn time_dependent_dijkstra_should_return_none_if_unreachable() {
+    let graph = graph();
+
+    assert!(time_dependent_dijkstra(&graph, &HashMap::new(), 3, 0, 0.0).is_none());
+    assert!(time_dependent_dijkstra(&graph, &HashMap::new(), 0, 9, 0.0).is_none());
+}
+
+#[test]
+fn time_dependent_dijkstra_should_return_none_for_nan_departure() {
+    assert!(time_dependent_dijkstra(&graph(), &HashMap::from([(2, ferry())]), 0, 3, f32::NAN).is_none());
+}
+
+#[test]
+fn time_dependent_dijkstra_should_skip_nan_weights() {
+    let graph = Graph::from(Vec::from([
+        Edge::from(0, 0, 1, f32::NAN),
+        Edge::from(1, 0, 1, 2.0),
+    ]));
+
+    let route = time_dependent_dijkstra(&graph, &HashMap::new(), 0, 1, 0.0).unwrap();
+
+    assert_eq!(vec![(0, 0.0), (1, 2.0)], route.arrivals);
+}
+
+#[test]
+fn time_dependent_dijkstra_should_handle_source_as_target() {
+    let route = time_dependent_dijkstra(&graph(), &HashMap::new(), 1, 1, 3.0).unwrap();
+
+    assert!(route.path.edges.is_empty());
+    assert_eq!(vec![(1, 3.0)], route.arrivals);
+}
@@ -1,3 +1,7 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct UnionFind {
     sizes: Vec<usize>,
     ids: Vec<usize>,
@@ -21,7 +25,7 @@ impl UnionFind {
         };
     }
 
-    fn find(&mut self, mut p: usize) -> usize {
+    pub fn find(&mut self, mut p: usize) -> usize {
         let mut root = p;
 
         while root != self.ids[root] {
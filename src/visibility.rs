@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::graph::{Edge, Graph};
+use crate::node::Vec3;
+use crate::path;
+use crate::search::{AStar, euclidean_distance};
+
+const EPSILON: f32 = 1e-6;
+
+// Graph of the obstacle corners which see each other, for exact shortest paths around polygons in the x-y plane.
+// Node ids are the corner positions in the order of the obstacles and their vertices.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VisibilityGraph {
+    pub obstacles: Vec<Vec<Vec3>>,
+    vertices: Vec<Vec3>,
+    edges: Vec<Edge>,
+}
+
+impl VisibilityGraph {
+    pub fn from(obstacles: Vec<Vec<Vec3>>) -> VisibilityGraph {
+        let vertices: Vec<Vec3> = obstacles.iter().flatten().cloned().collect();
+        let mut visibility = VisibilityGraph { obstacles, vertices, edges: Vec::new() };
+
+        let mut edges = Vec::new();
+        for from in 0..visibility.vertices.len() {
+            for to in from + 1..visibility.vertices.len() {
+                let (start, end) = (&visibility.vertices[from], &visibility.vertices[to]);
+                if visibility.visible(start, end) {
+                    let distance = start.euclidean_dist(end);
+                    edges.push(Edge::from(edges.len(), from, to, distance));
+                    edges.push(Edge::from(edges.len(), to, from, distance));
+                }
+            }
+        }
+
+        visibility.edges = edges;
+        return visibility;
+    }
+
+    pub fn vertices(&self) -> &[Vec3] {
+        return &self.vertices;
+    }
+
+    // The segment may run along obstacle sides and touch corners, but not cross a side or an obstacle.
+    pub fn visible(&self, from: &Vec3, to: &Vec3) -> bool {
+        for obstacle in &self.obstacles {
+            for (start, end) in sides_of(obstacle) {
+                if crosses(from, to, &start, &end) {
+                    return false;
+                }
+            }
+        }
+
+        // split the segment at the corners it touches, every piece must stay outside the obstacles
+        let mut shares = vec![0.0, 1.0];
+        shares.extend(self.vertices.iter()
+            .filter(|vertex| on_segment(from, to, vertex))
+            .map(|vertex| share_along(from, to, vertex)));
+        shares.sort_by(|share1, share2| share1.total_cmp(share2));
+
+        return shares.windows(2).all(|pair| {
+            let middle = interpolate(from, to, (pair[0] + pair[1]) / 2.0);
+            return !self.obstacles.iter().any(|obstacle| inside(obstacle, &middle));
+        });
+    }
+
+    pub fn graph(&self) -> Graph {
+        let mut graph = Graph::from(self.edges.clone());
+        graph.offer_positions(self.vertices.iter().cloned().enumerate().collect());
+        return graph;
+    }
+
+    // Graph with start and goal added as the nodes after the corners, returned with their node ids.
+    pub fn with_endpoints(&self, start: &Vec3, goal: &Vec3) -> (Graph, usize, usize) {
+        let (start_id, goal_id) = (self.vertices.len(), self.vertices.len() + 1);
+        let mut edges = self.edges.clone();
+
+        for (node_id, vertex) in self.vertices.iter().enumerate() {
+            if self.visible(start, vertex) {
+                edges.push(Edge::from(edges.len(), start_id, node_id, start.euclidean_dist(vertex)));
+            }
+            if self.visible(vertex, goal) {
+                edges.push(Edge::from(edges.len(), node_id, goal_id, vertex.euclidean_dist(goal)));
+            }
+        }
+        if self.visible(start, goal) {
+            edges.push(Edge::from(edges.len(), start_id, goal_id, start.euclidean_dist(goal)));
+        }
+
+        let mut positions: HashMap<usize, Vec3> = self.vertices.iter().cloned().enumerate().collect();
+        positions.insert(start_id, start.clone());
+        positions.insert(goal_id, goal.clone());
+
+        let mut graph = Graph::from(edges);
+        graph.offer_positions(positions);
+        return (graph, start_id, goal_id);
+    }
+
+    // Shortest path from start to goal including both, found with A* and the euclidean distance.
+    pub fn find_path(&self, start: &Vec3, goal: &Vec3) -> Option<Vec<Vec3>> {
+        let (graph, start_id, goal_id) = self.with_endpoints(start, goal);
+        let found = path::in_graph(start_id, goal_id, &graph, Box::from(AStar { heuristic: Box::from(euclidean_distance) }));
+
+        if found.edges.is_empty() {
+            return None;
+        }
+
+        let mut points = vec![start.clone()];
+        points.extend(found.edges.iter().map(|edge| graph.get_position(&edge.destination).clone()));
+        return Some(points);
+    }
+}
+
+fn sides_of(vertices: &[Vec3]) -> Vec<(Vec3, Vec3)> {
+    return (0..vertices.len())
+        .map(|index| (vertices[index].clone(), vertices[(index + 1) % vertices.len()].clone()))
+        .collect();
+}
+
+// Positive, if the point is left of the line from start to end.
+fn cross(start: &Vec3, end: &Vec3, point: &Vec3) -> f32 {
+    return (end.x - start.x) * (point.y - start.y) - (end.y - start.y) * (point.x - start.x);
+}
+
+// Proper intersection, touching at an end point does not count.
+fn crosses(from: &Vec3, to: &Vec3, start: &Vec3, end: &Vec3) -> bool {
+    return cross(from, to, start) * cross(from, to, end) < -EPSILON
+        && cross(start, end, from) * cross(start, end, to) < -EPSILON;
+}
+
+fn share_along(from: &Vec3, to: &Vec3, point: &Vec3) -> f32 {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    return ((point.x - from.x) * dx + (point.y - from.y) * dy) / (dx * dx + dy * dy);
+}
+
+fn on_segment(from: &Vec3, to: &Vec3, point: &Vec3) -> bool {
+    if cross(from, to, point).abs() > EPSILON {
+        return false;
+    }
+
+    let share = share_along(from, to, point);
+    return share > 0.0 && share < 1.0;
+}
+
+fn interpolate(from: &Vec3, to: &Vec3, share: f32) -> Vec3 {
+    return Vec3::from(from.x + share * (to.x - from.x), from.y + share * (to.y - from.y), from.z + share * (to.z - from.z));
+}
+
+// Strictly inside, points on a side are outside.
+fn inside(polygon: &[Vec3], point: &Vec3) -> bool {
+    let sides = sides_of(polygon);
+    if sides.iter().any(|(start, end)| on_segment(start, end, point) || start == point) {
+        return false;
+    }
+
+    let crossings = sides.iter()
+        .filter(|(start, end)| (start.y > point.y) != (end.y > point.y)
+            && point.x < start.x + (point.y - start.y) / (end.y - start.y) * (end.x - start.x))
+        .count();
+    return crossings % 2 == 1;
+}
+
+
+#[cfg(test)]
+fn rectangle(x1: f32, y1: f32, x2: f32, y2: f32) -> Vec<Vec3> {
+    return vec![
+        Vec3::from(x1, y1, 0.0),
+        Vec3::from(x2, y1, 0.0),
+        Vec3::from(x2, y2, 0.0),
+        Vec3::from(x1, y2, 0.0),
+    ];
+}
+
+#[test]
+fn visibility_should_be_blocked_by_obstacles() {
+    let visibility = VisibilityGraph::from(vec![rectangle(1.0, 1.0, 3.0, 3.0)]);
+
+    assert!(visibility.visible(&Vec3::from(0.0, 0.0, 0.0), &Vec3::from(4.0, 0.0, 0.0)));
+    assert!(visibility.visible(&Vec3::from(0.0, 1.0, 0.0), &Vec3::from(4.0, 1.0, 0.0)));
+    assert!(!visibility.visible(&Vec3::from(0.0, 2.0, 0.0), &Vec3::from(4.0, 2.0, 0.0)));
+    assert!(!visibility.visible(&Vec3::from(0.0, 0.0, 0.0), &Vec3::from(4.0, 4.0, 0.0)));
+    assert!(!visibility.visible(&Vec3::from(1.0, 1.0, 0.0), &Vec3::from(3.0, 3.0, 0.0)));
+}
+
+#[test]
+fn visibility_graph_should_connect_visible_corners() {
+    let graph = VisibilityGraph::from(vec![rectangle(1.0, 1.0, 3.0, 3.0)]).graph();
+
+    // the sides in both directions, no diagonals
+    assert_eq!(8, graph.edges.len());
+    assert!(graph.edges.iter().all(|edge| edge.weight == 2.0));
+    assert_eq!(&Vec3::from(3.0, 3.0, 0.0), graph.get_position(&2));
+}
+
+#[test]
+fn visibility_graph_should_find_path_around_obstacle() {
+    let visibility = VisibilityGraph::from(vec![rectangle(1.0, 1.0, 3.0, 3.0), rectangle(1.0, 3.5, 3.0, 5.0)]);
+
+    let path = visibility.find_path(&Vec3::from(0.0, 2.0, 0.0), &Vec3::from(4.0, 2.0, 0.0)).unwrap();
+    let length: f32 = path.windows(2).map(|pair| pair[0].euclidean_dist(&pair[1])).sum();
+
+    assert_eq!(4, path.len());
+    assert!((length - (2.0 + 2.0 * 2.0_f32.sqrt())).abs() < 1e-5);
+}
+
+#[test]
+fn visibility_graph_should_connect_visible_endpoints_directly() {
+    let visibility = VisibilityGraph::from(vec![rectangle(1.0, 1.0, 3.0, 3.0)]);
+
+    let (graph, start, goal) = visibility.with_endpoints(&Vec3::from(0.0, 0.0, 0.0), &Vec3::from(4.0, 0.0, 0.0));
+    let path = path::in_graph(start, goal, &graph, Box::from(AStar { heuristic: Box::from(euclidean_distance) }));
+
+    assert_eq!((4, 5), (start, goal));
+    assert_eq!(1, path.edges.len());
+    assert!(visibility.find_path(&Vec3::from(0.0, 0.0, 0.0), &Vec3::from(2.0, 2.0, 0.0)).is_none());
+}